@@ -12,7 +12,7 @@ fn main() {
 fn load_prefab(mut cmd: Commands) {
     let prefab =
         cmd.load_prefab::<ValuePrefab, ValueFormatter, _>("assets/prefab/custom_prefab.ron");
-    prefab.spawn_empty();
+    prefab.prepare_spawn_empty();
 }
 
 fn assert_is_loaded(query: Query<&Value>) {
@@ -34,9 +34,8 @@ impl IntoComponent for ValuePrefab {
 
 struct ValueFormatter;
 impl Format<ValuePrefab> for ValueFormatter {
-    fn load_from_bytes(bytes: Vec<u8>) -> Result<Prefab<ValuePrefab>, Box<dyn std::error::Error>> {
-        let mut de = ron::de::Deserializer::from_bytes(&bytes)?;
-        let valor = ValuePrefab::deserialize(&mut de)?;
+    fn load_from_bytes(bytes: Vec<u8>) -> Result<Prefab<ValuePrefab>, PrefabError> {
+        let valor: ValuePrefab = from_ron_bytes(&bytes)?;
         let prefab = Prefab::from_data(Some(valor));
 
         Ok(prefab)
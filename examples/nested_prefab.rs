@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+fn main() {
+    App::new()
+        .add_systems(Startup, setup)
+        .add_systems(Update, assert_is_loaded)
+        .run();
+}
+
+#[derive(Component)]
+struct Marker;
+fn setup(mut cmd: Commands) {
+    // The house references the door and window prefabs, they are loaded with the same format
+    let mut prefab =
+        cmd.load_prefab::<ValuePrefab, RonPrefabFormat<_>, _>("assets/prefab/house/house.ron");
+    // Named entities can be found by their path, even if the file changes
+    if let Some(knob) = prefab.get_entity_by_path_mut("door/0/knob") {
+        knob.set_data(ValuePrefab(13));
+    }
+    prefab.prepare_spawn(Marker);
+}
+
+fn assert_is_loaded(
+    query_parent: Query<(Entity, &PrefabInstance), With<Marker>>,
+    children: Query<&Children>,
+    value_query: Query<(&Value, Option<&Name>)>,
+) {
+    let (entity, instance) = query_parent.get_single().unwrap();
+    // The root knows the entity spawned for each entity of the prefab
+    let knob = instance.entity_by_name("knob").unwrap();
+    println!("Knob {:?} of the house {:?}", knob, instance.source());
+
+    if let Ok((v, name)) = value_query.get(entity) {
+        println!("House {name:?} with value: {}", v.0);
+    }
+
+    for child in children.iter_descendants(entity) {
+        if let Ok((v, name)) = value_query.get(child) {
+            println!("Child {name:?} with value: {}", v.0);
+        }
+    }
+}
+
+#[derive(Component)]
+struct Value(i32);
+#[derive(Deserialize, Serialize)]
+struct ValuePrefab(i32);
+impl IntoComponent for ValuePrefab {
+    type Component = Value;
+    fn into_component(self) -> Self::Component {
+        Value(self.0)
+    }
+}
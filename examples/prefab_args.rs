@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+fn main() {
+    App::new()
+        .add_systems(Startup, setup)
+        .add_systems(Update, assert_is_loaded)
+        .run();
+}
+
+#[derive(Component)]
+struct Marker;
+fn setup(mut cmd: Commands) {
+    // The same file gives a different enemy for each argument
+    let args = PrefabArgs::new().with("title", "Boss").with("hp", 50);
+    let prefab = cmd.load_prefab::<EnemyPrefab, RonPrefabFormat<_>, _>(
+        "assets/prefab/enemies/enemy_template.ron",
+    );
+    if let Some(prefab) = prefab.with_args(args) {
+        prefab.prepare_spawn(Marker);
+    }
+}
+
+fn assert_is_loaded(
+    query_parent: Query<Entity, With<Marker>>,
+    children: Query<&Children>,
+    enemy_query: Query<(Option<&Health>, Option<&Title>)>,
+) {
+    let entity = query_parent.get_single().unwrap();
+    for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+        if let Ok((health, title)) = enemy_query.get(entity) {
+            println!(
+                "Entity {:?} with health {:?}",
+                title.map(|t| t.0.as_str()),
+                health.map(|h| h.0)
+            );
+        }
+    }
+}
+
+#[derive(Component)]
+struct Health(i32);
+// A number can only be given by an argument inside a Param
+#[derive(Deserialize, Serialize)]
+struct HealthPrefab(Param<i32>);
+impl IntoComponent for HealthPrefab {
+    type Component = Health;
+    fn into_component(self) -> Self::Component {
+        Health(self.0.into_value().unwrap_or_default())
+    }
+}
+
+#[derive(Component)]
+struct Title(String);
+#[derive(Deserialize, Serialize)]
+struct TitlePrefab(String);
+impl IntoComponent for TitlePrefab {
+    type Component = Title;
+    fn into_component(self) -> Self::Component {
+        Title(self.0)
+    }
+}
+
+#[derive(PrefabData, Deserialize, Serialize)]
+struct EnemyPrefab {
+    health: Option<HealthPrefab>,
+    title: Option<TitlePrefab>,
+}
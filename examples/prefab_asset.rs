@@ -0,0 +1,69 @@
+use bevy::{asset::LoadState, prelude::*};
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+fn main() {
+    App::new()
+        .add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .add_plugins(PrefabAssetPlugin::<ValuePrefab, ValueFormatter>::new(&[
+            "ron",
+        ]))
+        .add_systems(Startup, load_prefab)
+        .add_systems(Update, (spawn_when_loaded, assert_is_loaded))
+        .run();
+}
+
+#[derive(Resource)]
+struct ValueHandle(Handle<PrefabAsset<ValuePrefab>>);
+
+fn load_prefab(mut cmd: Commands, asset_server: Res<AssetServer>) {
+    // The path is relative to the asset folder
+    let handle = asset_server.load("prefab/custom_prefab.ron");
+    cmd.insert_resource(ValueHandle(handle));
+}
+
+fn spawn_when_loaded(
+    mut cmd: Commands,
+    handle: Option<Res<ValueHandle>>,
+    asset_server: Res<AssetServer>,
+    mut prefabs: ResMut<Assets<PrefabAsset<ValuePrefab>>>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+    if asset_server.load_state(&handle.0) != LoadState::Loaded {
+        return;
+    }
+    if let Some(asset) = prefabs.remove(&handle.0) {
+        cmd.prepare_and_spawn_prefab(asset.into_prefab());
+    }
+    cmd.remove_resource::<ValueHandle>();
+}
+
+fn assert_is_loaded(query: Query<&Value, Added<Value>>) {
+    for value in query.iter() {
+        println!("Loaded value: {}", value.0)
+    }
+}
+
+#[derive(Component)]
+struct Value(i32);
+
+#[derive(Deserialize, Serialize)]
+struct ValuePrefab(i32);
+impl IntoComponent for ValuePrefab {
+    type Component = Value;
+    fn into_component(self) -> Self::Component {
+        Value(self.0)
+    }
+}
+
+struct ValueFormatter;
+impl Format<ValuePrefab> for ValueFormatter {
+    fn load_from_bytes(bytes: Vec<u8>) -> Result<Prefab<ValuePrefab>, PrefabError> {
+        let valor: ValuePrefab = from_ron_bytes(&bytes)?;
+        let prefab = Prefab::from_data(Some(valor));
+
+        Ok(prefab)
+    }
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+fn main() {
+    App::new()
+        .add_systems(Startup, setup)
+        .add_systems(Update, assert_is_loaded)
+        .run();
+}
+
+#[derive(Component)]
+struct Room;
+fn setup(mut cmd: Commands) {
+    // The file has many roots, each one is spawned as a child of the room
+    let room = cmd.spawn(Room).id();
+    let prefab = cmd.load_prefab::<ValuePrefab, RonPrefabFormat<_>, _>("assets/prefab/room.ron");
+    let roots = prefab.prepare_spawn_forest_as_children_of(room);
+    println!("Spawned {} roots", roots.len());
+}
+
+fn assert_is_loaded(
+    query_room: Query<Entity, With<Room>>,
+    children: Query<&Children>,
+    value_query: Query<(&Name, &Value)>,
+) {
+    let room = query_room.get_single().unwrap();
+    for root in children.get(room).into_iter().flatten() {
+        if let Ok((name, v)) = value_query.get(*root) {
+            println!("Root {} with value: {}", name, v.0);
+        }
+        for child in children.iter_descendants(*root) {
+            if let Ok((name, v)) = value_query.get(child) {
+                println!("Child {} with value: {}", name, v.0);
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct Value(i32);
+#[derive(Deserialize, Serialize)]
+struct ValuePrefab(i32);
+impl IntoComponent for ValuePrefab {
+    type Component = Value;
+    fn into_component(self) -> Self::Component {
+        Value(self.0)
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+fn main() {
+    App::new()
+        .add_systems(Startup, setup)
+        .add_systems(Update, (spawn_bullets, assert_is_loaded).chain())
+        .run();
+}
+
+/// The prefab shared by all the bullets, it is never consumed
+#[derive(Resource)]
+struct BulletPrefab(Arc<Prefab<ValuePrefab, Prepared>>);
+
+fn setup(world: &mut World) {
+    let prefab = PrefabLoader::create_prefab::<ValuePrefab, RonPrefabFormat<_>, _>(
+        "assets/prefab/ron_prefab.ron",
+    )
+    .unwrap();
+    // Only prepared prefabs can be shared, the assets are loaded once for all the bullets
+    let prefab = prefab.prepare_entities(world).unwrap();
+    world.insert_resource(BulletPrefab(Arc::new(prefab)));
+}
+
+fn spawn_bullets(mut cmd: Commands, bullet: Res<BulletPrefab>, mut frames: Local<u32>) {
+    *frames += 1;
+    if *frames > 3 {
+        return;
+    }
+    // Each bullet is a copy of the prefab, only the data of the entities is cloned
+    let transforms = (0..5).map(|i| Transform::from_xyz(i as f32, *frames as f32, 0.));
+    cmd.spawn_prefab_instances(&bullet.0, transforms);
+}
+
+fn assert_is_loaded(instances: Query<&Transform, With<PrefabInstance>>, values: Query<&Value>) {
+    let total: i32 = values.iter().map(|v| v.0).sum();
+    println!(
+        "{} bullets, with total value {total}",
+        instances.iter().count()
+    );
+}
+
+#[derive(Component)]
+struct Value(i32);
+#[derive(Clone, Deserialize, Serialize)]
+struct ValuePrefab(i32);
+impl IntoComponent for ValuePrefab {
+    type Component = Value;
+    fn into_component(self) -> Self::Component {
+        Value(self.0)
+    }
+}
@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+fn main() {
+    App::new()
+        .add_systems(Startup, setup)
+        .add_systems(Update, assert_is_loaded)
+        .run();
+}
+
+#[derive(Component)]
+struct Marker;
+fn setup(mut cmd: Commands) {
+    let prefab = cmd.load_prefab::<TurretPrefab, RonPrefabFormat<_>, _>("assets/prefab/turret.ron");
+    prefab.prepare_spawn(Marker);
+}
+
+fn assert_is_loaded(
+    query_parent: Query<Entity, With<Marker>>,
+    children: Query<&Children>,
+    aim_query: Query<(&Name, &Aim)>,
+    names: Query<&Name>,
+) {
+    let entity = query_parent.get_single().unwrap();
+    for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+        if let Ok((name, aim)) = aim_query.get(entity) {
+            println!("{} aims at {}", name, names.get(aim.0).unwrap());
+        }
+    }
+}
+
+#[derive(Component)]
+struct Aim(Entity);
+#[derive(Deserialize, Serialize)]
+struct AimPrefab(PrefabRef);
+impl IntoComponent for AimPrefab {
+    type Component = Aim;
+    fn into_component(self) -> Self::Component {
+        Aim(self.0.entity().unwrap_or(Entity::PLACEHOLDER))
+    }
+    fn resolve_refs(&mut self, instance: &PrefabInstance) {
+        self.0.resolve(instance);
+    }
+    fn remap_refs(&mut self, map: &dyn Fn(usize) -> Option<usize>) {
+        self.0.remap(map);
+    }
+}
+
+#[derive(PrefabData, Deserialize, Serialize)]
+struct TurretPrefab {
+    aim: Option<AimPrefab>,
+}
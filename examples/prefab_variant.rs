@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+fn main() {
+    App::new()
+        .add_systems(Startup, setup)
+        .add_systems(Update, assert_is_loaded)
+        .run();
+}
+
+#[derive(Component)]
+struct Marker;
+fn setup(mut cmd: Commands) {
+    // The archer is the goblin with a few changes
+    let prefab = cmd.load_prefab::<EnemyPrefab, RonPrefabFormat<_>, _>(
+        "assets/prefab/enemies/goblin_archer.ron",
+    );
+    prefab.prepare_spawn(Marker);
+}
+
+fn assert_is_loaded(
+    query_parent: Query<Entity, With<Marker>>,
+    children: Query<&Children>,
+    enemy_query: Query<(Option<&Health>, Option<&Speed>)>,
+) {
+    let entity = query_parent.get_single().unwrap();
+    for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+        if let Ok((health, speed)) = enemy_query.get(entity) {
+            println!(
+                "Entity with health {:?} and speed {:?}",
+                health.map(|h| h.0),
+                speed.map(|s| s.0)
+            );
+        }
+    }
+}
+
+#[derive(Component)]
+struct Health(i32);
+#[derive(Deserialize, Serialize)]
+struct HealthPrefab(i32);
+impl IntoComponent for HealthPrefab {
+    type Component = Health;
+    fn into_component(self) -> Self::Component {
+        Health(self.0)
+    }
+}
+
+#[derive(Component)]
+struct Speed(i32);
+#[derive(Deserialize, Serialize)]
+struct SpeedPrefab(i32);
+impl IntoComponent for SpeedPrefab {
+    type Component = Speed;
+    fn into_component(self) -> Self::Component {
+        Speed(self.0)
+    }
+}
+
+// The fields of a variant are merged into the base, `None` keeps the value of the base
+#[derive(PrefabData, Deserialize, Serialize)]
+struct EnemyPrefab {
+    health: Option<HealthPrefab>,
+    speed: Option<SpeedPrefab>,
+}
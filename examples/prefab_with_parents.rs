@@ -14,7 +14,7 @@ struct Marker;
 fn setup(mut cmd: Commands) {
     let prefab =
         cmd.load_prefab::<ValuePrefab, ValueFormatter, _>("assets/prefab/prefab_with_parents.ron");
-    prefab.spawn(Marker);
+    prefab.prepare_spawn(Marker);
 }
 
 fn assert_is_loaded(
@@ -62,9 +62,8 @@ enum ValueTree {
 
 struct ValueFormatter;
 impl Format<ValuePrefab> for ValueFormatter {
-    fn load_from_bytes(bytes: Vec<u8>) -> Result<Prefab<ValuePrefab>, Box<dyn std::error::Error>> {
-        let mut de = ron::de::Deserializer::from_bytes(&bytes)?;
-        let valor = ValueTree::deserialize(&mut de)?;
+    fn load_from_bytes(bytes: Vec<u8>) -> Result<Prefab<ValuePrefab>, PrefabError> {
+        let valor: ValueTree = from_ron_bytes(&bytes)?;
         let mut prefab = Prefab::new();
         value_tree(valor, &mut prefab, 0, 0);
         Ok(prefab)
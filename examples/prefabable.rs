@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use bevy_prfb::{components::ui::UiRectPrefab, *};
+use serde::{Deserialize, Serialize};
+
+fn main() {
+    App::new()
+        .add_systems(Startup, setup)
+        .add_systems(Update, assert_is_loaded)
+        .run();
+}
+
+#[derive(Component)]
+struct Marker;
+fn setup(mut cmd: Commands) {
+    let prefab = cmd.load_prefab::<TowerPrefab, RonPrefabFormat<_>, _>("assets/prefab/tower.ron");
+    prefab.prepare_spawn(Marker);
+}
+
+fn assert_is_loaded(
+    query_parent: Query<Entity, With<Marker>>,
+    children: Query<&Children>,
+    health_query: Query<&Health>,
+    frame_query: Query<&Frame>,
+) {
+    let entity = query_parent.get_single().unwrap();
+    for entity in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+        if let Ok(health) = health_query.get(entity) {
+            println!("health {}/{}", health.current, health.max);
+        }
+        if let Ok(frame) = frame_query.get(entity) {
+            println!("{} with margin {:?}", frame.label, frame.margin.left);
+        }
+    }
+}
+
+// Generates `HealthPrefab`, the missing fields are taken from `Health::default`
+#[derive(Component, Prefabable)]
+#[prefabable(default)]
+struct Health {
+    current: i32,
+    max: i32,
+}
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            current: 10,
+            max: 10,
+        }
+    }
+}
+
+// Generates `FramePrefab`, the margin is given as an `UiRectPrefab`
+#[derive(Component, Prefabable)]
+struct Frame {
+    #[prefabable(mirror = UiRectPrefab, default)]
+    margin: UiRect,
+    label: String,
+    #[prefabable(skip)]
+    _hovered: bool,
+}
+
+#[derive(PrefabData, Deserialize, Serialize)]
+struct TowerPrefab {
+    health: Option<HealthPrefab>,
+    frame: Option<FramePrefab>,
+}
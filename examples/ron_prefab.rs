@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+fn main() {
+    App::new()
+        .add_systems(Startup, setup)
+        .add_systems(Update, assert_is_loaded)
+        .run();
+}
+
+#[derive(Component)]
+struct Marker;
+fn setup(mut cmd: Commands) {
+    // No custom formatter needed, the file follows the RonPrefab schema
+    let prefab =
+        cmd.load_prefab::<ValuePrefab, RonPrefabFormat<_>, _>("assets/prefab/ron_prefab.ron");
+    prefab.prepare_spawn(Marker);
+}
+
+fn assert_is_loaded(
+    query_parent: Query<Entity, With<Marker>>,
+    children: Query<&Children>,
+    value_query: Query<&Value>,
+) {
+    let entity = query_parent.get_single().unwrap();
+    if let Ok(v) = value_query.get(entity) {
+        println!("Parent with value: {}", v.0);
+    }
+
+    for child in children.iter_descendants(entity) {
+        if let Ok(v) = value_query.get(child) {
+            println!("Child with value: {}", v.0);
+        }
+    }
+}
+
+#[derive(Component)]
+struct Value(i32);
+#[derive(Deserialize, Serialize)]
+struct ValuePrefab(i32);
+impl IntoComponent for ValuePrefab {
+    type Component = Value;
+    fn into_component(self) -> Self::Component {
+        Value(self.0)
+    }
+}
@@ -49,6 +49,7 @@ impl CustomWidget for MyCustomWidget {
                     children.push(take_ui(ui_to_repeat.clone(), i))
                 }
                 Ui::Container {
+                    name: None,
                     node,
                     children,
                     custom_data,
@@ -69,6 +70,7 @@ struct UiData {
 
 fn take_ui(child: MyUi, i: usize) -> MyUi {
     if let MyUi::Text {
+        name,
         mut text_data,
         custom_data,
     } = child
@@ -78,6 +80,7 @@ fn take_ui(child: MyUi, i: usize) -> MyUi {
             text.text.push_str(format!(" {i}").as_str());
         }
         MyUi::Text {
+            name,
             text_data,
             custom_data,
         }
@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+use bevy_prfb::{
+    ui::{UiData, UiPrefabPlugin},
+    PrefabAsset, PrefabAssetCommandsExt, PrefabAssetSpawnPlugin,
+};
+
+// Run with `cargo run --example hot_reload_ui --features file_watcher`
+// and edit assets/ui/menu.ron while the example is running
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(AssetPlugin {
+            watch_for_changes_override: Some(true),
+            ..Default::default()
+        }))
+        .add_plugins(UiPrefabPlugin::default().with_extensions(&["ron"]))
+        .add_plugins(PrefabAssetSpawnPlugin::<UiData>::new().with_hot_reload())
+        .add_systems(Startup, setup)
+        .run();
+}
+
+#[derive(Component)]
+pub struct MainMenu;
+
+fn setup(mut cmd: Commands, asset_server: Res<AssetServer>) {
+    cmd.spawn(Camera2dBundle::default());
+    let menu = asset_server.load::<PrefabAsset<UiData>>("ui/menu.ron");
+    cmd.spawn_prefab_asset_with(menu, MainMenu);
+}
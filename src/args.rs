@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use bevy::log::warn;
+use serde::{
+    de::{DeserializeOwned, Error as _},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_value::Value;
+
+use crate::error::PrefabError;
+
+/// A value of any serializable type, given to a parameter of the prefab
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct ArgValue(Value);
+impl ArgValue {
+    pub fn new<V: Serialize>(value: V) -> Result<Self, PrefabError> {
+        serde_value::to_value(value)
+            .map(Self)
+            .map_err(|e| PrefabError::Custom(Box::new(e)))
+    }
+
+    pub(crate) fn value(&self) -> &Value {
+        &self.0
+    }
+}
+
+/// The arguments given to the parameters of a prefab when spawning, by name (without the `$`).
+/// See [`Prefab::apply_args`](crate::prefab::Prefab::apply_args)
+#[derive(Clone, Debug, Default)]
+pub struct PrefabArgs {
+    values: HashMap<String, ArgValue>,
+}
+impl PrefabArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give the value to the parameter, a value that can't be serialized is logged and skipped
+    pub fn with<V: Serialize>(mut self, name: impl Into<String>, value: V) -> Self {
+        let name = name.into();
+        if let Err(e) = self.insert(name.clone(), value) {
+            warn!(target: "prefab", "[warn] The argument {name} couldn't be serialized: {e}");
+        }
+        self
+    }
+
+    pub fn insert<V: Serialize>(
+        &mut self,
+        name: impl Into<String>,
+        value: V,
+    ) -> Result<(), PrefabError> {
+        self.values.insert(name.into(), ArgValue::new(value)?);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ArgValue> {
+        self.values.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+}
+
+/// A field that can be given by an argument, written as `"$name"` in the file.
+///
+/// String fields don't need it, `"$name"` is replaced by the argument in any of them.
+/// Only the declared parameters are replaced, other `$` strings are kept.
+///
+/// The value is read by a self-describing format, so it can't be an enum in RON files
+#[derive(Clone, Debug)]
+pub enum Param<T> {
+    Value(T),
+    Arg(String),
+}
+impl<T> Param<T> {
+    /// The value, None if the argument wasn't applied
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Arg(_) => None,
+        }
+    }
+
+    pub fn into_value(self) -> Option<T> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Arg(_) => None,
+        }
+    }
+}
+impl<T> From<T> for Param<T> {
+    fn from(value: T) -> Self {
+        Self::Value(value)
+    }
+}
+impl<T: Serialize> Serialize for Param<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Value(value) => value.serialize(serializer),
+            Self::Arg(name) => serializer.serialize_str(&format!("${name}")),
+        }
+    }
+}
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Param<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let valor = Value::deserialize(deserializer)?;
+        if let Value::String(text) = &valor {
+            if let Some(name) = arg_name(text) {
+                return Ok(Self::Arg(name.to_string()));
+            }
+        }
+        T::deserialize(valor)
+            .map(Self::Value)
+            .map_err(D::Error::custom)
+    }
+}
+
+// `"$$name"` escapes a string that would be the parameter `name`
+fn arg_name(text: &str) -> Option<&str> {
+    text.strip_prefix('$').filter(|name| !name.starts_with('$'))
+}
+
+/// Replace every `"$name"` in the data of the entity by its argument, through a serde round trip.
+/// The lookup gives None for a name that isn't a parameter, and `Some(None)` for a missing argument
+pub(crate) fn substitute<T: Serialize + DeserializeOwned>(
+    entity: usize,
+    data: &T,
+    lookup: &dyn Fn(&str) -> Option<Option<Value>>,
+) -> Result<T, PrefabError> {
+    let invalid = |reason: String| PrefabError::InvalidEntity { entity, reason };
+    let valor = serde_value::to_value(data).map_err(|e| invalid(e.to_string()))?;
+    let valor = replace(valor, lookup)?;
+    T::deserialize(valor).map_err(|e| invalid(e.to_string()))
+}
+
+fn replace(
+    valor: Value,
+    lookup: &dyn Fn(&str) -> Option<Option<Value>>,
+) -> Result<Value, PrefabError> {
+    Ok(match valor {
+        Value::String(text) => match (arg_name(&text), text.strip_prefix('$')) {
+            (Some(name), _) => match lookup(name) {
+                Some(valor) => valor.ok_or_else(|| PrefabError::MissingArg(name.to_string()))?,
+                None => Value::String(text),
+            },
+            (None, Some(escaped))
+                if escaped
+                    .strip_prefix('$')
+                    .is_some_and(|n| lookup(n).is_some()) =>
+            {
+                Value::String(escaped.to_string())
+            }
+            _ => Value::String(text),
+        },
+        Value::Option(Some(valor)) => Value::Option(Some(Box::new(replace(*valor, lookup)?))),
+        Value::Newtype(valor) => Value::Newtype(Box::new(replace(*valor, lookup)?)),
+        Value::Seq(valores) => Value::Seq(
+            valores
+                .into_iter()
+                .map(|valor| replace(valor, lookup))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Map(valores) => Value::Map(
+            valores
+                .into_iter()
+                .map(|(key, valor)| Ok((key, replace(valor, lookup)?)))
+                .collect::<Result<_, PrefabError>>()?,
+        ),
+        other => other,
+    })
+}
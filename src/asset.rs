@@ -0,0 +1,426 @@
+use std::{
+    collections::HashSet,
+    io,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::{
+        io::Reader, Asset, AssetApp, AssetEvent, AssetLoader, AssetPath, AssetServer, Assets,
+        AsyncReadExt, Handle, LoadContext, LoadState,
+    },
+    ecs::{
+        component::Component,
+        event::EventReader,
+        system::{Commands, Query, Res, Resource},
+        world::World,
+    },
+    log::warn,
+    prelude::{App, Bundle, Entity, Plugin, Update},
+    reflect::{utility::GenericTypePathCell, TypePath},
+    utils::BoxedFuture,
+};
+
+use crate::{
+    error::PrefabError,
+    prefab::{
+        give_up_spawn, resolve_reference, respawn_prefab, Format, Prefab, PrefabData,
+        PrefabFailurePolicy, PrepareAndSpawnPrefab,
+    },
+};
+
+/// A [`Prefab`] loaded through the [`AssetServer`](bevy::asset::AssetServer).
+///
+/// The asset keeps the prefab untouched, clone it (or take it out of `Assets`) before spawning,
+/// since spawning consumes the data of every entity.
+#[derive(Asset)]
+pub struct PrefabAsset<PD: PrefabData> {
+    pub prefab: Prefab<PD>,
+}
+impl<PD: PrefabData> PrefabAsset<PD> {
+    pub fn new(prefab: Prefab<PD>) -> Self {
+        Self { prefab }
+    }
+
+    pub fn prefab(&self) -> &Prefab<PD> {
+        &self.prefab
+    }
+
+    pub fn prefab_mut(&mut self) -> &mut Prefab<PD> {
+        &mut self.prefab
+    }
+
+    pub fn into_prefab(self) -> Prefab<PD> {
+        self.prefab
+    }
+}
+impl<PD: PrefabData + Clone> Clone for PrefabAsset<PD> {
+    fn clone(&self) -> Self {
+        Self {
+            prefab: self.prefab.clone(),
+        }
+    }
+}
+// PrefabData types aren't required to implement `TypePath`, so the path is built from the type name
+impl<PD: PrefabData> TypePath for PrefabAsset<PD> {
+    fn type_path() -> &'static str {
+        static CELL: GenericTypePathCell = GenericTypePathCell::new();
+        CELL.get_or_insert::<Self, _>(|| {
+            format!(
+                "bevy_prfb::asset::PrefabAsset<{}>",
+                std::any::type_name::<PD>()
+            )
+        })
+    }
+
+    fn short_type_path() -> &'static str {
+        static CELL: GenericTypePathCell = GenericTypePathCell::new();
+        CELL.get_or_insert::<Self, _>(|| format!("PrefabAsset<{}>", std::any::type_name::<PD>()))
+    }
+
+    fn type_ident() -> Option<&'static str> {
+        Some("PrefabAsset")
+    }
+
+    fn crate_name() -> Option<&'static str> {
+        Some("bevy_prfb")
+    }
+
+    fn module_path() -> Option<&'static str> {
+        Some("bevy_prfb::asset")
+    }
+}
+
+/// The [`AssetLoader`] that turns the bytes of a file into a [`PrefabAsset`] with the given [`Format`]
+/// - PD: The prefab data that all the entities are bound for.
+/// - F: The formatter for loading the prefab.
+pub struct PrefabAssetLoader<PD, F> {
+    extensions: &'static [&'static str],
+    marker: PhantomData<(PD, F)>,
+}
+impl<PD: PrefabData, F: Format<PD>> PrefabAssetLoader<PD, F> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<PD: PrefabData, F: Format<PD> + 'static> AssetLoader for PrefabAssetLoader<PD, F> {
+    type Asset = PrefabAsset<PD>;
+    type Settings = ();
+    type Error = PrefabError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let path = load_context.path().to_path_buf();
+            let mut prefab = read_prefab::<PD, F>(bytes, &path)?;
+            let mut ancestors = vec![path.clone()];
+            resolve_nested::<PD, F>(load_context, &mut prefab, &path, &mut ancestors).await?;
+            prefab.debug_validate_forest()?;
+            prefab.set_source(load_context.asset_path().to_string());
+            Ok(PrefabAsset::new(prefab))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
+
+// Like `PrefabLoader::resolve_references`, but the files are read as dependencies of the asset,
+// so changing them also reloads it
+async fn resolve_nested<PD: PrefabData, F: Format<PD>>(
+    load_context: &mut LoadContext<'_>,
+    prefab: &mut Prefab<PD>,
+    path: &Path,
+    ancestors: &mut Vec<PathBuf>,
+) -> Result<(), PrefabError> {
+    if let Some(base) = prefab.take_base() {
+        let base_path = resolve_reference(path, &base);
+        let base = load_nested::<PD, F>(load_context, base_path, ancestors).await?;
+        *prefab = base.apply_variant(std::mem::take(prefab))?;
+    }
+    for (index, reference) in prefab.take_references() {
+        let reference_path = resolve_reference(path, &reference);
+        let nested = load_nested::<PD, F>(load_context, reference_path, ancestors).await?;
+        prefab.graft(index, nested);
+    }
+    Ok(())
+}
+
+// Boxed, since it is recursive
+fn load_nested<'a, 'b: 'a, PD: PrefabData, F: Format<PD>>(
+    load_context: &'a mut LoadContext<'b>,
+    path: PathBuf,
+    ancestors: &'a mut Vec<PathBuf>,
+) -> BoxedFuture<'a, Result<Prefab<PD>, PrefabError>> {
+    Box::pin(async move {
+        if ancestors.contains(&path) {
+            return Err(PrefabError::ReferenceCycle(path));
+        }
+        // Same source of the prefab being loaded
+        let source = load_context.asset_path().source().clone_owned();
+        let asset_path = AssetPath::from_path(&path).with_source(source);
+        let bytes = load_context
+            .read_asset_bytes(asset_path)
+            .await
+            .map_err(|e| PrefabError::Io {
+                path: Some(path.clone()),
+                error: io::Error::other(e),
+            })?;
+        let mut prefab = read_prefab::<PD, F>(bytes, &path)?;
+
+        ancestors.push(path.clone());
+        resolve_nested::<PD, F>(load_context, &mut prefab, &path, ancestors).await?;
+        ancestors.pop();
+        Ok(prefab)
+    })
+}
+
+fn read_prefab<PD: PrefabData, F: Format<PD>>(
+    bytes: Vec<u8>,
+    path: &Path,
+) -> Result<Prefab<PD>, PrefabError> {
+    // The bytes are kept to show where the error is
+    F::load_from_bytes(bytes.clone()).map_err(|e| e.with_source(path, &bytes))
+}
+
+/// Register the [`PrefabAsset`] of PD and its [`PrefabAssetLoader`] for the given extensions.
+///
+/// Only one loader can be registered per extension, so give each format its own
+/// (`"enemy.ron"`, `"ui.ron"`...).
+///
+/// It must be added after the `AssetPlugin` (or the `DefaultPlugins`), without it nothing is registered.
+pub struct PrefabAssetPlugin<PD, F> {
+    extensions: &'static [&'static str],
+    marker: PhantomData<(PD, F)>,
+}
+impl<PD: PrefabData, F: Format<PD>> PrefabAssetPlugin<PD, F> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<PD: PrefabData, F: Format<PD> + 'static> Plugin for PrefabAssetPlugin<PD, F> {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<AssetServer>() {
+            warn!(
+                target: "prefab",
+                "[warn] PrefabAssetPlugin added without the AssetServer, add it after the AssetPlugin to load prefabs as assets"
+            );
+            return;
+        }
+        // Many formats can share the same prefab data
+        if !app.world.contains_resource::<Assets<PrefabAsset<PD>>>() {
+            app.init_asset::<PrefabAsset<PD>>();
+        }
+        app.register_asset_loader(PrefabAssetLoader::<PD, F>::new(self.extensions));
+    }
+}
+
+/// Root of a prefab spawned from a [`PrefabAsset`].
+///
+/// The prefab is prepared and spawned under this entity as soon as the asset is loaded,
+/// this requires the [`PrefabAssetSpawnPlugin`] of PD.
+/// If the asset can't be loaded, the [`PrefabFailurePolicy`] is applied to the entity
+#[derive(Component)]
+pub struct PrefabAssetInstance<PD: PrefabData> {
+    pub handle: Handle<PrefabAsset<PD>>,
+    spawned: bool,
+    failed: bool,
+    /// The reloads left, once the asset failed
+    retry: Option<u32>,
+}
+impl<PD: PrefabData> PrefabAssetInstance<PD> {
+    pub fn new(handle: Handle<PrefabAsset<PD>>) -> Self {
+        Self {
+            handle,
+            spawned: false,
+            failed: false,
+            retry: None,
+        }
+    }
+
+    /// If the prefab was already spawned under the entity
+    pub fn is_spawned(&self) -> bool {
+        self.spawned
+    }
+
+    /// If the asset couldn't be loaded, so the prefab will never be spawned
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+}
+
+pub trait PrefabAssetCommandsExt {
+    /// Spawn the prefab of the asset once it is loaded
+    fn spawn_prefab_asset<PD: PrefabData + Clone>(
+        &mut self,
+        handle: Handle<PrefabAsset<PD>>,
+    ) -> Entity;
+
+    /// Spawn the prefab of the asset once it is loaded, with a bundle in the parent
+    fn spawn_prefab_asset_with<PD: PrefabData + Clone, B: Bundle>(
+        &mut self,
+        handle: Handle<PrefabAsset<PD>>,
+        bundle: B,
+    ) -> Entity;
+}
+
+impl<'w, 's> PrefabAssetCommandsExt for Commands<'w, 's> {
+    fn spawn_prefab_asset<PD: PrefabData + Clone>(
+        &mut self,
+        handle: Handle<PrefabAsset<PD>>,
+    ) -> Entity {
+        self.spawn(PrefabAssetInstance::new(handle)).id()
+    }
+
+    fn spawn_prefab_asset_with<PD: PrefabData + Clone, B: Bundle>(
+        &mut self,
+        handle: Handle<PrefabAsset<PD>>,
+        bundle: B,
+    ) -> Entity {
+        self.spawn((PrefabAssetInstance::new(handle), bundle)).id()
+    }
+}
+
+/// Spawn the [`PrefabAssetInstance`]s of PD when their asset is loaded.
+///
+/// With hot reload enabled, every instance is respawned when the source of its asset changes.
+/// The `AssetServer` must be watching for changes for that, see the `file_watcher` feature.
+pub struct PrefabAssetSpawnPlugin<PD> {
+    hot_reload: bool,
+    marker: PhantomData<PD>,
+}
+impl<PD: PrefabData + Clone> PrefabAssetSpawnPlugin<PD> {
+    pub fn new() -> Self {
+        Self {
+            hot_reload: false,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn with_hot_reload(mut self) -> Self {
+        self.hot_reload = true;
+        self
+    }
+}
+impl<PD: PrefabData + Clone> Default for PrefabAssetSpawnPlugin<PD> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<PD: PrefabData + Clone> Plugin for PrefabAssetSpawnPlugin<PD> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PrefabAssetSpawnSettings::<PD> {
+            hot_reload: self.hot_reload,
+            marker: PhantomData,
+        })
+        .add_systems(Update, spawn_prefab_asset_instances::<PD>);
+    }
+}
+
+#[derive(Resource)]
+pub struct PrefabAssetSpawnSettings<PD> {
+    pub hot_reload: bool,
+    marker: PhantomData<PD>,
+}
+
+pub fn spawn_prefab_asset_instances<PD: PrefabData + Clone>(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<PrefabAsset<PD>>>,
+    prefabs: Res<Assets<PrefabAsset<PD>>>,
+    asset_server: Res<AssetServer>,
+    policy: Option<Res<PrefabFailurePolicy>>,
+    settings: Res<PrefabAssetSpawnSettings<PD>>,
+    mut instances: Query<(Entity, &mut PrefabAssetInstance<PD>)>,
+) {
+    let modified: HashSet<_> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, mut instance) in instances.iter_mut() {
+        let reload =
+            instance.spawned && settings.hot_reload && modified.contains(&instance.handle.id());
+        if instance.failed || (instance.spawned && !reload) {
+            continue;
+        }
+        let Some(asset) = prefabs.get(&instance.handle) else {
+            if asset_server.load_state(&instance.handle) == LoadState::Failed {
+                retry_or_fail_instance(
+                    &mut commands,
+                    &asset_server,
+                    policy.as_deref(),
+                    entity,
+                    &mut instance,
+                );
+            }
+            // Not yet loaded
+            continue;
+        };
+        let prefab = asset.prefab.clone();
+        if reload {
+            // Components that were removed from the root in the file will stay in the root
+            commands.add(move |world: &mut World| respawn_prefab(world, entity, prefab));
+        } else {
+            commands.add(PrepareAndSpawnPrefab::new(prefab, entity));
+        }
+        instance.spawned = true;
+    }
+}
+
+// Reload the failed asset while the policy retries, only the frames it failed are counted.
+// Then the failure is reported once and the instance is left alone
+fn retry_or_fail_instance<PD: PrefabData>(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    policy: Option<&PrefabFailurePolicy>,
+    entity: Entity,
+    instance: &mut PrefabAssetInstance<PD>,
+) {
+    let retry = match instance.retry {
+        Some(frames) => frames.checked_sub(1),
+        None => match policy {
+            Some(PrefabFailurePolicy::Retry { frames, .. }) => frames.checked_sub(1),
+            _ => None,
+        },
+    };
+    if let (Some(frames), Some(path)) = (retry, instance.handle.path()) {
+        asset_server.reload(path.clone());
+        instance.retry = Some(frames);
+        return;
+    }
+
+    instance.failed = true;
+    let failed = instance.handle.clone().untyped();
+    let path = match failed.path() {
+        Some(path) => path.to_string(),
+        None => format!("{:?}", failed.id()),
+    };
+    commands.add(move |world: &mut World| {
+        give_up_spawn::<PD>(
+            world,
+            entity,
+            vec![failed],
+            PrefabError::AssetLoad(vec![path]),
+        );
+    });
+}
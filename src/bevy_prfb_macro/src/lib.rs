@@ -1,51 +1,533 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, spanned::Spanned, DeriveInput, Index};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, DeriveInput};
 
-#[proc_macro_derive(PrefabData)]
+#[proc_macro_derive(PrefabData, attributes(prefab))]
 pub fn derive_system_param(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
-    let syn::Data::Struct(syn::DataStruct {
-        fields: field_definitions,
-        ..
-    }) = ast.data
-    else {
-        return syn::Error::new(ast.span(), "Invalid `PrefabData` type: expected a `struct`")
-            .into_compile_error()
-            .into();
-    };
+    match prefab_data_methods(&ast) {
+        Ok(methods) => {
+            let struct_name = &ast.ident;
+            let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+            TokenStream::from(quote! {
+                const _: () = {
+                    impl #impl_generics bevy_prfb::prefab::PrefabData for #struct_name #ty_generics #where_clause {
+                        #methods
+                    }
+                };
+            })
+        }
+        Err(e) => e.into_compile_error().into(),
+    }
+}
 
-    let mut fields = Vec::new();
-    for (i, field) in field_definitions.iter().enumerate() {
-        let i = Index::from(i);
-        fields.push(
-            field
-                .ident
-                .as_ref()
-                .map(|f| quote! { #f })
-                .unwrap_or_else(|| quote! { #i }),
-        );
-    }
-    let struct_name = &ast.ident;
-    let generics = ast.generics;
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+/// How a field is given to the entity, by `#[prefab(..)]`
+enum FieldKind {
+    /// The field is PrefabData
+    Data,
+    /// `#[prefab(skip)]`, the field is ignored
+    Skip,
+    /// `#[prefab(with = path::to_fn)]`, the function inserts the field
+    With(syn::Path),
+    /// `#[prefab(component)]`, the field is inserted as it is
+    Insert,
+    /// `#[prefab(bundle)]`, the field is inserted as it is, but can't be read back
+    Bundle,
+}
+
+#[derive(Default)]
+struct PrefabAttributes {
+    marker: Option<syn::Path>,
+    kind: Option<FieldKind>,
+    /// `#[prefab(extract = path::to_fn)]`, the function reads the field back from the entity
+    extract: Option<syn::Path>,
+}
+
+fn prefab_attributes(attrs: &[syn::Attribute]) -> syn::Result<PrefabAttributes> {
+    let mut resultado = PrefabAttributes::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("prefab")) {
+        attr.parse_nested_meta(|meta| {
+            let kind = if meta.path.is_ident("marker") {
+                resultado.marker = Some(meta.value()?.parse()?);
+                return Ok(());
+            } else if meta.path.is_ident("extract") {
+                resultado.extract = Some(meta.value()?.parse()?);
+                return Ok(());
+            } else if meta.path.is_ident("skip") {
+                FieldKind::Skip
+            } else if meta.path.is_ident("with") {
+                FieldKind::With(meta.value()?.parse()?)
+            } else if meta.path.is_ident("component") {
+                FieldKind::Insert
+            } else if meta.path.is_ident("bundle") {
+                FieldKind::Bundle
+            } else {
+                return Err(meta.error(
+                    "unsupported `prefab` attribute, expected `skip`, `with`, `component`, `bundle`, `extract` or `marker`",
+                ));
+            };
+            if resultado.kind.replace(kind).is_some() {
+                return Err(meta.error("only one of `skip`, `with`, `component` or `bundle` can be given"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(resultado)
+}
+
+// The marker is given to the struct, the enum or its variants, the kind to the fields
+fn marker(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::Path>> {
+    let attributes = prefab_attributes(attrs)?;
+    if attributes.kind.is_some() || attributes.extract.is_some() {
+        let attr = attrs.iter().find(|attr| attr.path().is_ident("prefab"));
+        return Err(syn::Error::new(
+            attr.span(),
+            "`skip`, `with`, `component`, `bundle` and `extract` are given to the fields",
+        ));
+    }
+    Ok(attributes.marker)
+}
+
+fn field_kind(field: &syn::Field) -> syn::Result<(FieldKind, Option<syn::Path>)> {
+    let attributes = prefab_attributes(&field.attrs)?;
+    if let Some(marker) = attributes.marker {
+        return Err(syn::Error::new(
+            marker.span(),
+            "`marker` is given to the struct, the enum or its variants",
+        ));
+    }
+    Ok((
+        attributes.kind.unwrap_or(FieldKind::Data),
+        attributes.extract,
+    ))
+}
+
+type Variant<'a> = (proc_macro2::TokenStream, &'a syn::Fields, Option<syn::Path>);
+
+// Structs are handled as an enum of a single variant
+fn variants<'a>(ast: &'a DeriveInput, derive: &str) -> syn::Result<Vec<Variant<'a>>> {
+    match &ast.data {
+        syn::Data::Struct(data) => Ok(vec![(quote! { Self }, &data.fields, None)]),
+        syn::Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                Ok((
+                    quote! { Self::#ident },
+                    &variant.fields,
+                    marker(&variant.attrs)?,
+                ))
+            })
+            .collect(),
+        syn::Data::Union(_) => Err(syn::Error::new(
+            ast.span(),
+            format!("Invalid `{derive}` type: expected a `struct` or an `enum`"),
+        )),
+    }
+}
+
+// Every variant forwards to its fields
+fn prefab_data_methods(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let variants = variants(ast, "PrefabData")?;
+    let marker = marker(&ast.attrs)?.map(|marker| quote! { entity.insert(#marker); });
 
-    TokenStream::from(quote! {
-        const _: () = {
-            impl #impl_generics bevy_prfb::prefab::PrefabData for #struct_name #ty_generics #where_clause {
-                fn insert_into_entity(self, entity: &mut bevy::ecs::world::EntityWorldMut) {
-                    #(
-                        self.#fields.insert_into_entity(entity);
-                    )*
+    let mut insert = Vec::new();
+    let mut load = Vec::new();
+    let mut collect = Vec::new();
+    let mut resolve = Vec::new();
+    let mut remap = Vec::new();
+    let mut merge = Vec::new();
+    for (path, fields, variant_marker) in variants {
+        let variant_marker = variant_marker.map(|marker| quote! { entity.insert(#marker); });
+        let own = bindings(fields, "field");
+        let others = bindings(fields, "other");
+        let own_pattern = pattern(&path, fields, &own);
+        let other_pattern = pattern(&path, fields, &others);
+
+        let (mut inserted, mut loaded, mut merged) = (Vec::new(), Vec::new(), Vec::new());
+        for ((field, binding), other) in fields.iter().zip(&own).zip(&others) {
+            match field_kind(field)?.0 {
+                FieldKind::Data => {
+                    inserted.push(quote! { #binding.insert_into_entity(entity); });
+                    loaded.push(binding);
+                    merged.push(quote! { #binding.merge(#other); });
+                }
+                FieldKind::Skip => {}
+                FieldKind::With(function) => {
+                    inserted.push(quote! { #function(#binding, entity); });
+                    merged.push(quote! { *#binding = #other; });
+                }
+                FieldKind::Insert | FieldKind::Bundle => {
+                    inserted.push(quote! { entity.insert(#binding); });
+                    merged.push(quote! { *#binding = #other; });
                 }
-                fn load_sub_assets(&mut self, world: &mut bevy::ecs::world::World) -> bool {
-                    let mut loaded = false;
-                    #(
-                        loaded |= self.#fields.load_sub_assets(world);
-                    )*
-                    loaded
+            }
+        }
+        insert.push(quote! {
+            #own_pattern => {
+                #variant_marker
+                #( #inserted )*
+            }
+        });
+        load.push(quote! {
+            #own_pattern => {
+                let mut loaded = false;
+                #( loaded |= #loaded.load_sub_assets(world); )*
+                loaded
+            }
+        });
+        collect.push(quote! {
+            #own_pattern => {
+                #( #loaded.collect_sub_assets(handles); )*
+            }
+        });
+        resolve.push(quote! {
+            #own_pattern => {
+                #( bevy_prfb::prefab::PrefabData::resolve_refs(#loaded, instance); )*
+            }
+        });
+        remap.push(quote! {
+            #own_pattern => {
+                #( bevy_prfb::prefab::PrefabData::remap_refs(#loaded, map); )*
+            }
+        });
+        merge.push(quote! {
+            (#own_pattern, #other_pattern) => {
+                #( #merged )*
+            }
+        });
+    }
+    Ok(quote! {
+        #[allow(unused_variables)]
+        fn insert_into_entity(self, entity: &mut bevy::ecs::world::EntityWorldMut) {
+            #marker
+            match self {
+                #( #insert )*
+            }
+        }
+        #[allow(unused_variables, unused_mut)]
+        fn load_sub_assets(&mut self, world: &mut bevy::ecs::world::World) -> bool {
+            match self {
+                #( #load )*
+            }
+        }
+        #[allow(unused_variables)]
+        fn collect_sub_assets(&self, handles: &mut Vec<bevy::asset::UntypedHandle>) {
+            match self {
+                #( #collect )*
+            }
+        }
+        #[allow(unused_variables)]
+        fn resolve_refs(&mut self, instance: &bevy_prfb::prefab::PrefabInstance) {
+            match self {
+                #( #resolve )*
+            }
+        }
+        #[allow(unused_variables)]
+        fn remap_refs(&mut self, map: &dyn Fn(usize) -> Option<usize>) {
+            match self {
+                #( #remap )*
+            }
+        }
+        #[allow(unused_variables, unreachable_patterns)]
+        fn merge(&mut self, other: Self) {
+            match (self, other) {
+                #( #merge )*
+                // Another variant replaces the data
+                (data, other) => *data = other,
+            }
+        }
+    })
+}
+
+fn bindings(fields: &syn::Fields, prefix: &str) -> Vec<syn::Ident> {
+    (0..fields.len())
+        .map(|i| format_ident!("{}_{}", prefix, i))
+        .collect()
+}
+
+fn pattern(
+    path: &proc_macro2::TokenStream,
+    fields: &syn::Fields,
+    bindings: &[syn::Ident],
+) -> proc_macro2::TokenStream {
+    match fields {
+        syn::Fields::Named(named) => {
+            let names = named.named.iter().map(|f| &f.ident);
+            quote! { #path { #( #names: #bindings ),* } }
+        }
+        syn::Fields::Unnamed(_) => quote! { #path ( #( #bindings ),* ) },
+        syn::Fields::Unit => quote! { #path },
+    }
+}
+
+#[proc_macro_derive(ExtractPrefabData, attributes(prefab))]
+pub fn derive_extract_prefab_data(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    match extract_prefab_data(&ast) {
+        Ok(method) => {
+            let struct_name = &ast.ident;
+            let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+            TokenStream::from(quote! {
+                const _: () = {
+                    impl #impl_generics bevy_prfb::prefab::ExtractPrefabData for #struct_name #ty_generics #where_clause {
+                        #method
+                    }
+                };
+            })
+        }
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+// The first variant whose marker and fields are found in the entity is extracted
+fn extract_prefab_data(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let variants = variants(ast, "ExtractPrefabData")?;
+    let marker = marker(&ast.attrs)?.map(|marker| {
+        quote! {
+            if !entity.contains::<#marker>() {
+                return None;
+            }
+        }
+    });
+
+    let mut extracted = Vec::new();
+    for (path, fields, variant_marker) in variants {
+        let variant_marker = variant_marker.map(|marker| {
+            quote! {
+                if !entity.contains::<#marker>() {
+                    return None;
                 }
             }
+        });
+        let values = fields
+            .iter()
+            .map(extract_field)
+            .collect::<syn::Result<Vec<_>>>()?;
+        let value = match fields {
+            syn::Fields::Named(named) => {
+                let names = named.named.iter().map(|f| &f.ident);
+                quote! { #path { #( #names: #values ),* } }
+            }
+            syn::Fields::Unnamed(_) => quote! { #path ( #( #values ),* ) },
+            syn::Fields::Unit => quote! { #path },
+        };
+        extracted.push(quote! {
+            let variant = (|| -> Option<Self> {
+                #variant_marker
+                Some(#value)
+            })();
+            if variant.is_some() {
+                return variant;
+            }
+        });
+    }
+    Ok(quote! {
+        #[allow(clippy::redundant_closure_call)]
+        fn extract_from_entity(entity: &bevy::ecs::world::EntityRef) -> Option<Self> {
+            #marker
+            #( #extracted )*
+            None
+        }
+    })
+}
+
+fn extract_field(field: &syn::Field) -> syn::Result<proc_macro2::TokenStream> {
+    let ty = &field.ty;
+    let (kind, extract) = field_kind(field)?;
+    if let Some(extract) = extract {
+        return Ok(quote! { #extract(entity)? });
+    }
+    match kind {
+        FieldKind::Data => Ok(
+            quote! { <#ty as bevy_prfb::prefab::ExtractPrefabData>::extract_from_entity(entity)? },
+        ),
+        FieldKind::Skip => Ok(quote! { Default::default() }),
+        FieldKind::Insert => Ok(quote! { entity.get::<#ty>()?.clone() }),
+        FieldKind::With(function) => Err(syn::Error::new(
+            function.span(),
+            "a `with` field is read back with `extract = path::to_fn`",
+        )),
+        FieldKind::Bundle => Err(syn::Error::new(
+            field.span(),
+            "a `bundle` field is read back with `extract = path::to_fn`",
+        )),
+    }
+}
+
+/// Generate the serde mirror `{Component}Prefab` of a component, with its `IntoComponent` and
+/// `FromComponent` impls. The fields of the mirror are cloned from the component.
+///
+/// The attributes are `#[prefabable(..)]`, so `#[prefab(..)]` is left to `PrefabData`.
+/// - `#[prefabable(default)]` on the struct: the mirror is `Default` like the component, and missing fields are defaulted.
+/// - `#[prefabable(default)]` on a field: the field is defaulted when missing.
+/// - `#[prefabable(mirror = Type)]` on a field: the field is given as `Type` in the mirror, converted with `Into`
+///   (and `From` the field of the component).
+/// - `#[prefabable(skip)]` on a field: the field isn't in the mirror, it receives its `Default`.
+#[proc_macro_derive(Prefabable, attributes(prefabable))]
+pub fn derive_prefabable(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    match prefabable(&ast) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct MirrorAttributes {
+    default: bool,
+    skip: bool,
+    mirror: Option<syn::Type>,
+}
+
+fn mirror_attributes(attrs: &[syn::Attribute]) -> syn::Result<MirrorAttributes> {
+    let mut resultado = MirrorAttributes::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("prefabable")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                resultado.default = true;
+            } else if meta.path.is_ident("skip") {
+                resultado.skip = true;
+            } else if meta.path.is_ident("mirror") {
+                resultado.mirror = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error(
+                    "unsupported `prefabable` attribute, expected `default`, `skip` or `mirror`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(resultado)
+}
+
+fn prefabable(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let syn::Data::Struct(data) = &ast.data else {
+        return Err(syn::Error::new(
+            ast.span(),
+            "Invalid `Prefabable` type: expected a `struct`",
+        ));
+    };
+    let attributes = mirror_attributes(&ast.attrs)?;
+    if attributes.skip || attributes.mirror.is_some() {
+        return Err(syn::Error::new(
+            ast.span(),
+            "`skip` and `mirror` are given to the fields",
+        ));
+    }
+
+    let mut fields = Vec::new();
+    let mut values = Vec::new();
+    let mut defaults = Vec::new();
+    for field in data.fields.iter() {
+        let field_attributes = mirror_attributes(&field.attrs)?;
+        let name = field
+            .ident
+            .as_ref()
+            .map(|f| quote! { #f: })
+            .unwrap_or_default();
+        if field_attributes.skip {
+            values.push(quote! { #name Default::default() });
+            continue;
+        }
+        // Tuple structs lose the index of the skipped fields
+        let member = field
+            .ident
+            .as_ref()
+            .map(|f| quote! { #f })
+            .unwrap_or_else(|| {
+                let index = syn::Index::from(fields.len());
+                quote! { #index }
+            });
+        let own = field
+            .ident
+            .as_ref()
+            .map(|f| quote! { #f })
+            .unwrap_or_else(|| {
+                let index = syn::Index::from(values.len());
+                quote! { #index }
+            });
+        let (value, default) = match field_attributes.mirror {
+            Some(_) => (
+                quote! { self.#member.into() },
+                quote! { component.#own.clone().into() },
+            ),
+            None => (quote! { self.#member }, quote! { component.#own.clone() }),
         };
+        values.push(quote! { #name #value });
+        defaults.push(quote! { #name #default });
+
+        let docs = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"));
+        let default = field_attributes
+            .default
+            .then(|| quote! { #[serde(default)] });
+        let vis = &field.vis;
+        let ty = field_attributes.mirror.unwrap_or_else(|| field.ty.clone());
+        fields.push(quote! { #(#docs)* #default #vis #name #ty });
+    }
+
+    let component = &ast.ident;
+    let mirror = format_ident!("{}Prefab", component);
+    let vis = &ast.vis;
+    let generics = &ast.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // Serde only defaults structs with fields
+    let serde_default = (attributes.default && !matches!(data.fields, syn::Fields::Unit))
+        .then(|| quote! { #[serde(default)] });
+    let doc = format!("The prefab data of [`{component}`], generated by `Prefabable`");
+    let (definition, constructor, from_component) = match &data.fields {
+        syn::Fields::Named(_) => (
+            quote! { #generics #where_clause { #(#fields,)* } },
+            quote! { #component { #(#values,)* } },
+            quote! { Self { #(#defaults,)* } },
+        ),
+        syn::Fields::Unnamed(_) => (
+            quote! { #generics ( #(#fields,)* ) #where_clause; },
+            quote! { #component ( #(#values,)* ) },
+            quote! { Self ( #(#defaults,)* ) },
+        ),
+        syn::Fields::Unit => (
+            quote! { #generics #where_clause; },
+            quote! { #component },
+            quote! { Self },
+        ),
+    };
+    // The default of the mirror is the default of the component
+    let default = attributes.default.then(|| {
+        quote! {
+            impl #impl_generics Default for #mirror #ty_generics #where_clause {
+                fn default() -> Self {
+                    let component = <#component #ty_generics as Default>::default();
+                    <Self as bevy_prfb::prefab::FromComponent>::from_component(&component)
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Clone, serde::Deserialize, serde::Serialize)]
+        #serde_default
+        #vis struct #mirror #definition
+
+        #default
+
+        impl #impl_generics bevy_prfb::prefab::IntoComponent for #mirror #ty_generics #where_clause {
+            type Component = #component #ty_generics;
+            fn into_component(self) -> Self::Component {
+                #constructor
+            }
+        }
+
+        impl #impl_generics bevy_prfb::prefab::FromComponent for #mirror #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn from_component(component: &Self::Component) -> Self {
+                #from_component
+            }
+        }
     })
 }
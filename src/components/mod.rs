@@ -6,7 +6,7 @@ pub use general::*;
 
 pub mod general {
     use bevy::{
-        asset::Handle,
+        asset::{Handle, UntypedHandle},
         math::{Quat, Vec2, Vec3},
         prelude::{Asset, AssetServer},
         render::{color::Color, view::Visibility},
@@ -16,7 +16,7 @@ pub mod general {
     };
     use serde::{Deserialize, Serialize};
 
-    use crate::prefab::IntoComponent;
+    use crate::prefab::{FromComponent, IntoComponent};
 
     #[derive(Default, Debug, Clone, Deserialize, Serialize)]
     pub enum AnchorPrefab {
@@ -47,6 +47,21 @@ pub mod general {
                 AnchorPrefab::Custom(c) => Anchor::Custom(c),
             }
         }
+
+        pub fn from_anchor(anchor: &Anchor) -> Self {
+            match anchor {
+                Anchor::Center => AnchorPrefab::Center,
+                Anchor::BottomLeft => AnchorPrefab::BottomLeft,
+                Anchor::BottomCenter => AnchorPrefab::BottomCenter,
+                Anchor::BottomRight => AnchorPrefab::BottomRight,
+                Anchor::CenterLeft => AnchorPrefab::CenterLeft,
+                Anchor::CenterRight => AnchorPrefab::CenterRight,
+                Anchor::TopLeft => AnchorPrefab::TopLeft,
+                Anchor::TopCenter => AnchorPrefab::TopCenter,
+                Anchor::TopRight => AnchorPrefab::TopRight,
+                Anchor::Custom(c) => AnchorPrefab::Custom(*c),
+            }
+        }
     }
     impl IntoComponent for AnchorPrefab {
         type Component = Anchor;
@@ -54,6 +69,11 @@ pub mod general {
             self.into_anchor()
         }
     }
+    impl FromComponent for AnchorPrefab {
+        fn from_component(component: &Self::Component) -> Self {
+            Self::from_anchor(component)
+        }
+    }
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
     pub enum TransformPrefab {
@@ -105,6 +125,15 @@ pub mod general {
             self.into_transform()
         }
     }
+    impl FromComponent for TransformPrefab {
+        fn from_component(component: &Self::Component) -> Self {
+            Self::Custom {
+                translation: component.translation.to_array(),
+                scale: component.scale.to_array(),
+                rotation: component.rotation.to_array(),
+            }
+        }
+    }
 
     /// Prefab used for loading external assets
     #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -121,10 +150,8 @@ pub mod general {
         pub fn load_self(&self, asset_server: &AssetServer) -> Option<Handle<A>> {
             match self {
                 Self::File(s) => Some(asset_server.load(s.to_owned())),
-                Self::Loaded(_) => {
-                    println!("[warn] Tried to load already loaded asset");
-                    None
-                }
+                // Preparing again keeps the loaded handle
+                Self::Loaded(h) => Some(h.clone()),
                 Self::None => Some(Default::default()),
             }
         }
@@ -140,6 +167,16 @@ pub mod general {
             }
         }
 
+        /// Push the handle, if loaded, so it can be waited for
+        pub fn collect_handle(&self, handles: &mut Vec<UntypedHandle>) {
+            if let Self::Loaded(h) = self {
+                // Default handles are weak and will never be loaded
+                if h.is_strong() {
+                    handles.push(h.clone().untyped());
+                }
+            }
+        }
+
         // If self is None, returns None
         pub fn into_option(self) -> Option<Self> {
             match self {
@@ -161,6 +198,18 @@ pub mod general {
                 _ => Some(self),
             }
         }
+
+        /// The counterpart of [`HandlePrefab::into_handle`].
+        /// Handles loaded from a path give back the path, so the prefab can be saved again
+        pub fn from_handle(handle: &Handle<A>) -> Self {
+            if *handle == Handle::default() {
+                Self::None
+            } else if let Some(path) = handle.path() {
+                Self::File(path.to_string())
+            } else {
+                Self::Loaded(handle.clone())
+            }
+        }
     }
     impl<A: Asset> Default for HandlePrefab<A> {
         fn default() -> Self {
@@ -186,6 +235,11 @@ pub mod general {
             BackgroundColor(self.0.into_color())
         }
     }
+    impl FromComponent for BackgroundColorPrefab {
+        fn from_component(component: &Self::Component) -> Self {
+            Self(ColorPrefab::from_color(component.0))
+        }
+    }
 
     #[derive(Debug, Clone, Deserialize, Serialize)]
     pub enum ColorPrefab {
@@ -203,6 +257,35 @@ pub mod general {
                 ColorPrefab::RgbaLinear(r, g, b, a) => Color::rgba_linear(r, g, b, a),
             }
         }
+
+        pub fn from_color(color: Color) -> Self {
+            match color {
+                Color::Rgba {
+                    red,
+                    green,
+                    blue,
+                    alpha,
+                } => ColorPrefab::Rgba(red, green, blue, alpha),
+                Color::RgbaLinear {
+                    red,
+                    green,
+                    blue,
+                    alpha,
+                } => ColorPrefab::RgbaLinear(red, green, blue, alpha),
+                Color::Hsla {
+                    hue,
+                    saturation,
+                    lightness,
+                    alpha,
+                } => ColorPrefab::Hsla(hue, saturation, lightness, alpha),
+                Color::Lcha {
+                    lightness,
+                    chroma,
+                    hue,
+                    alpha,
+                } => ColorPrefab::Lcha(lightness, chroma, hue, alpha),
+            }
+        }
     }
     impl Default for ColorPrefab {
         fn default() -> Self {
@@ -228,6 +311,11 @@ pub mod general {
             BorderColor(self.0.into_color())
         }
     }
+    impl FromComponent for BorderColorPrefab {
+        fn from_component(component: &Self::Component) -> Self {
+            Self(ColorPrefab::from_color(component.0))
+        }
+    }
 
     #[derive(Default, Clone, Debug, Deserialize, Serialize)]
     pub enum VisibilityPrefab {
@@ -251,4 +339,13 @@ pub mod general {
             self.into_visibility()
         }
     }
+    impl FromComponent for VisibilityPrefab {
+        fn from_component(component: &Self::Component) -> Self {
+            match component {
+                Visibility::Inherited => Self::Inherited,
+                Visibility::Hidden => Self::Hidden,
+                Visibility::Visible => Self::Visible,
+            }
+        }
+    }
 }
@@ -1,13 +1,17 @@
 use bevy::{
-    ecs::system::{In, RunSystemOnce},
+    asset::UntypedHandle,
+    ecs::{
+        system::{In, RunSystemOnce},
+        world::EntityRef,
+    },
     math::{Rect, Vec2},
-    prelude::{AssetServer, Res},
+    prelude::{AssetServer, Handle, Res},
     render::texture::Image,
     sprite::{Sprite, SpriteBundle},
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{ColorPrefab, IntoComponent, PrefabData};
+use crate::{ColorPrefab, ExtractPrefabData, FromComponent, IntoComponent, PrefabData};
 
 use super::general::{AnchorPrefab, HandlePrefab, TransformPrefab, VisibilityPrefab};
 
@@ -58,6 +62,19 @@ impl PrefabData for SpriteBundlePrefab {
             true
         }
     }
+    fn collect_sub_assets(&self, handles: &mut Vec<UntypedHandle>) {
+        self.texture.collect_handle(handles);
+    }
+}
+impl ExtractPrefabData for SpriteBundlePrefab {
+    fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+        Some(Self {
+            sprite: SpritePrefab::extract_from_entity(entidade)?,
+            texture: HandlePrefab::from_handle(entidade.get::<Handle<Image>>()?),
+            transform: TransformPrefab::extract_from_entity(entidade)?,
+            visibility: VisibilityPrefab::extract_from_entity(entidade)?,
+        })
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -96,3 +113,15 @@ impl IntoComponent for SpritePrefab {
         self.into_sprite()
     }
 }
+impl FromComponent for SpritePrefab {
+    fn from_component(component: &Self::Component) -> Self {
+        Self {
+            color: ColorPrefab::from_color(component.color),
+            flip_x: component.flip_x,
+            flip_y: component.flip_y,
+            custom_size: component.custom_size,
+            rect: component.rect,
+            anchor: AnchorPrefab::from_anchor(&component.anchor),
+        }
+    }
+}
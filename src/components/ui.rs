@@ -1,23 +1,24 @@
 use crate as bevy_prfb;
 use bevy::{
-    asset::AssetServer,
+    asset::{AssetServer, UntypedHandle},
+    ecs::world::EntityRef,
     log::warn,
     render::texture::Image,
-    text::{BreakLineOn, Font, Text, TextAlignment, TextSection, TextStyle},
+    text::{BreakLineOn, Font, Text, TextAlignment, TextLayoutInfo, TextSection, TextStyle},
     ui::{
         node_bundles::{ButtonBundle, ImageBundle, NodeBundle, TextBundle},
-        widget::Label,
+        widget::{Button, Label, UiImageSize},
         AlignContent, AlignItems, AlignSelf, Direction, Display, FlexDirection, FlexWrap,
         FocusPolicy, GridAutoFlow, GridPlacement, GridTrack, JustifyContent, JustifyItems,
-        JustifySelf, Overflow, PositionType, RepeatedGridTrack, Style, UiImage, UiRect, Val,
+        JustifySelf, Node, Overflow, PositionType, RepeatedGridTrack, Style, UiImage, UiRect, Val,
         ZIndex,
     },
 };
-use bevy_prfb_macro::PrefabData;
+use bevy_prfb_macro::{ExtractPrefabData, PrefabData};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    prefab::{IntoComponent, PrefabData},
+    prefab::{ExtractPrefabData, FromComponent, IntoComponent, PrefabData},
     ColorPrefab,
 };
 
@@ -61,6 +62,26 @@ impl PrefabData for NodeBundlePrefab {
         entidade.insert(node);
     }
 }
+impl ExtractPrefabData for NodeBundlePrefab {
+    fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+        // Every ui bundle has a Node, the others are told apart by the components only they have
+        if entidade.contains::<Button>()
+            || entidade.contains::<UiImageSize>()
+            || entidade.contains::<TextLayoutInfo>()
+        {
+            return None;
+        }
+        entidade.get::<Node>()?;
+        Some(Self {
+            style: StylePrefab::extract_from_entity(entidade)?,
+            background_color: BackgroundColorPrefab::extract_from_entity(entidade)?,
+            border_color: BorderColorPrefab::extract_from_entity(entidade)?,
+            focus_policy: FocusPolicy::extract_from_entity(entidade)?,
+            visibility: VisibilityPrefab::extract_from_entity(entidade)?,
+            z_index: ZIndexPrefab::extract_from_entity(entidade)?,
+        })
+    }
+}
 
 #[derive(Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
@@ -149,6 +170,51 @@ impl IntoComponent for StylePrefab {
         }
     }
 }
+impl FromComponent for StylePrefab {
+    fn from_component(component: &Self::Component) -> Self {
+        let component = component.clone();
+        Self {
+            display: component.display,
+            position_type: component.position_type,
+            overflow: component.overflow,
+            direction: component.direction,
+            left: component.left,
+            right: component.right,
+            top: component.top,
+            bottom: component.bottom,
+            width: component.width,
+            height: component.height,
+            min_width: component.min_width,
+            min_height: component.min_height,
+            max_width: component.max_width,
+            max_height: component.max_height,
+            aspect_ratio: component.aspect_ratio,
+            align_items: component.align_items,
+            justify_items: component.justify_items,
+            align_self: component.align_self,
+            justify_self: component.justify_self,
+            align_content: component.align_content,
+            justify_content: component.justify_content,
+            margin: UiRectPrefab::from_rect(component.margin),
+            padding: UiRectPrefab::from_rect(component.padding),
+            border: UiRectPrefab::from_rect(component.border),
+            flex_direction: component.flex_direction,
+            flex_wrap: component.flex_wrap,
+            flex_grow: component.flex_grow,
+            flex_shrink: component.flex_shrink,
+            flex_basis: component.flex_basis,
+            row_gap: component.row_gap,
+            column_gap: component.column_gap,
+            grid_auto_flow: component.grid_auto_flow,
+            grid_template_rows: component.grid_template_rows,
+            grid_template_columns: component.grid_template_columns,
+            grid_auto_rows: component.grid_auto_rows,
+            grid_auto_columns: component.grid_auto_columns,
+            grid_row: component.grid_row,
+            grid_column: component.grid_column,
+        }
+    }
+}
 
 #[derive(Clone, Deserialize, Serialize)]
 pub enum UiRectPrefab {
@@ -185,6 +251,37 @@ impl UiRectPrefab {
             },
         }
     }
+
+    fn from_rect(rect: UiRect) -> Self {
+        if rect.left == rect.right && rect.top == rect.bottom {
+            if rect.left == rect.top {
+                return Self::All(rect.left);
+            }
+            if rect.top == Val::ZERO {
+                return Self::Horizontal(rect.left);
+            }
+            if rect.left == Val::ZERO {
+                return Self::Vertical(rect.top);
+            }
+        }
+        Self::Custom {
+            right: rect.right,
+            top: rect.top,
+            left: rect.left,
+            bottom: rect.bottom,
+        }
+    }
+}
+// So it can be the mirror of an `UiRect` field in `Prefabable`
+impl From<UiRectPrefab> for UiRect {
+    fn from(value: UiRectPrefab) -> Self {
+        value.into_rect()
+    }
+}
+impl From<UiRect> for UiRectPrefab {
+    fn from(value: UiRect) -> Self {
+        Self::from_rect(value)
+    }
 }
 impl Default for UiRectPrefab {
     fn default() -> Self {
@@ -210,6 +307,14 @@ impl IntoComponent for ZIndexPrefab {
         }
     }
 }
+impl FromComponent for ZIndexPrefab {
+    fn from_component(component: &Self::Component) -> Self {
+        match component {
+            ZIndex::Local(l) => Self::Local(*l),
+            ZIndex::Global(g) => Self::Global(*g),
+        }
+    }
+}
 impl Default for ZIndexPrefab {
     fn default() -> Self {
         Self::Local(0)
@@ -268,6 +373,24 @@ impl PrefabData for TextBundlePrefab {
         };
         self.text.load(asset_server)
     }
+    fn collect_sub_assets(&self, handles: &mut Vec<UntypedHandle>) {
+        self.text.collect_handles(handles);
+    }
+}
+impl ExtractPrefabData for TextBundlePrefab {
+    fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+        if entidade.contains::<Button>() || !entidade.contains::<TextLayoutInfo>() {
+            return None;
+        }
+        Some(Self {
+            style: StylePrefab::extract_from_entity(entidade)?,
+            text: TextPrefab::extract_from_entity(entidade)?,
+            focus_policy: FocusPolicy::extract_from_entity(entidade)?,
+            visibility: VisibilityPrefab::extract_from_entity(entidade)?,
+            z_index: ZIndexPrefab::extract_from_entity(entidade)?,
+            background_color: BackgroundColorPrefab::extract_from_entity(entidade)?,
+        })
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -311,6 +434,35 @@ impl TextPrefab {
         }
         loaded
     }
+
+    pub fn collect_handles(&self, handles: &mut Vec<UntypedHandle>) {
+        self.default_font.collect_handle(handles);
+        for section in self.sections.iter() {
+            section.style.font.collect_handle(handles);
+        }
+    }
+
+    /// The counterpart of [`TextPrefab::into_text`], every section keeps its own font
+    pub fn from_text(text: &Text) -> Self {
+        let sections = text
+            .sections
+            .iter()
+            .map(|section| TextSectionPrefab {
+                text: section.value.clone(),
+                style: TextStylePrefab {
+                    font: HandlePrefab::from_handle(&section.style.font),
+                    font_size: section.style.font_size,
+                    color: ColorPrefab::from_color(section.style.color),
+                },
+            })
+            .collect();
+        Self {
+            sections,
+            alignment: text.alignment,
+            linebreak_behavior: text.linebreak_behavior,
+            default_font: HandlePrefab::None,
+        }
+    }
 }
 impl Default for TextPrefab {
     fn default() -> Self {
@@ -446,6 +598,26 @@ impl PrefabData for ImageBundlePrefab {
         };
         self.image.load(asset_server)
     }
+
+    fn collect_sub_assets(&self, handles: &mut Vec<UntypedHandle>) {
+        self.image.texture.collect_handle(handles);
+    }
+}
+impl ExtractPrefabData for ImageBundlePrefab {
+    fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+        if entidade.contains::<Button>() || !entidade.contains::<UiImageSize>() {
+            return None;
+        }
+        Some(Self {
+            style: StylePrefab::extract_from_entity(entidade)?,
+            background_color: BackgroundColorPrefab::extract_from_entity(entidade)?,
+            image: UiImagePrefab::extract_from_entity(entidade)?,
+            focus_policy: FocusPolicy::extract_from_entity(entidade)?,
+            transform: TransformPrefab::extract_from_entity(entidade)?,
+            visibility: VisibilityPrefab::extract_from_entity(entidade)?,
+            z_index: ZIndexPrefab::extract_from_entity(entidade)?,
+        })
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -479,6 +651,15 @@ impl UiImagePrefab {
             true
         }
     }
+
+    /// The counterpart of [`UiImagePrefab::into_image`]
+    pub fn from_image(image: &UiImage) -> Self {
+        Self {
+            texture: HandlePrefab::from_handle(&image.texture),
+            flip_x: image.flip_x,
+            flip_y: image.flip_y,
+        }
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -540,6 +721,30 @@ impl PrefabData for ButtonBundlePrefab {
         };
         ui_img.load(asset_server)
     }
+
+    fn collect_sub_assets(&self, handles: &mut Vec<UntypedHandle>) {
+        if let Some(ui_img) = self.image.as_ref() {
+            ui_img.texture.collect_handle(handles);
+        }
+    }
+}
+impl ExtractPrefabData for ButtonBundlePrefab {
+    fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+        entidade.get::<Button>()?;
+        // A button without image still has the default one
+        let image = UiImagePrefab::extract_from_entity(entidade)?;
+        let image = image.texture.into_option_ref().is_some().then_some(image);
+        Some(Self {
+            style: StylePrefab::extract_from_entity(entidade)?,
+            focus_policy: FocusPolicy::extract_from_entity(entidade)?,
+            background_color: BackgroundColorPrefab::extract_from_entity(entidade)?,
+            border_color: BorderColorPrefab::extract_from_entity(entidade)?,
+            image,
+            transform: TransformPrefab::extract_from_entity(entidade)?,
+            visibility: VisibilityPrefab::extract_from_entity(entidade)?,
+            z_index: ZIndexPrefab::extract_from_entity(entidade)?,
+        })
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -551,6 +756,11 @@ impl IntoComponent for LabelPrefab {
         Label
     }
 }
+impl FromComponent for LabelPrefab {
+    fn from_component(_component: &Self::Component) -> Self {
+        LabelPrefab
+    }
+}
 
 impl IntoComponent for FocusPolicy {
     type Component = Self;
@@ -558,8 +768,13 @@ impl IntoComponent for FocusPolicy {
         self
     }
 }
+impl FromComponent for FocusPolicy {
+    fn from_component(component: &Self::Component) -> Self {
+        *component
+    }
+}
 
-#[derive(PrefabData, Clone, Deserialize, Serialize)]
+#[derive(PrefabData, ExtractPrefabData, Clone, Deserialize, Serialize)]
 pub struct Common {
     style: StylePrefab,
     focus_policy: FocusPolicy,
@@ -582,6 +797,14 @@ impl PrefabData for TextPrefab {
         };
         self.load(asset_server)
     }
+    fn collect_sub_assets(&self, handles: &mut Vec<UntypedHandle>) {
+        self.collect_handles(handles);
+    }
+}
+impl ExtractPrefabData for TextPrefab {
+    fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+        entidade.get::<Text>().map(Self::from_text)
+    }
 }
 
 impl PrefabData for UiImagePrefab {
@@ -598,6 +821,15 @@ impl PrefabData for UiImagePrefab {
         };
         self.load(asset_server)
     }
+
+    fn collect_sub_assets(&self, handles: &mut Vec<UntypedHandle>) {
+        self.texture.collect_handle(handles);
+    }
+}
+impl ExtractPrefabData for UiImagePrefab {
+    fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+        entidade.get::<UiImage>().map(Self::from_image)
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize, PrefabData)]
@@ -605,3 +837,25 @@ pub struct General {
     text_data: Option<TextPrefab>,
     image_data: Option<UiImagePrefab>,
 }
+impl ExtractPrefabData for General {
+    fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+        // Only the text and image that no bundle owns
+        let text_data = if entidade.contains::<TextLayoutInfo>() {
+            None
+        } else {
+            TextPrefab::extract_from_entity(entidade)
+        };
+        let image_data = if entidade.contains::<Button>() || entidade.contains::<UiImageSize>() {
+            None
+        } else {
+            UiImagePrefab::extract_from_entity(entidade)
+        };
+        if text_data.is_none() && image_data.is_none() {
+            return None;
+        }
+        Some(Self {
+            text_data,
+            image_data,
+        })
+    }
+}
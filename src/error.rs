@@ -0,0 +1,227 @@
+use std::{
+    error::Error,
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::Entity;
+use ron::error::{Position, SpannedError};
+
+/// Everything that can go wrong while reading, writing or spawning a prefab
+#[derive(Debug)]
+pub enum PrefabError {
+    /// The file couldn't be read or written
+    Io {
+        path: Option<PathBuf>,
+        error: io::Error,
+    },
+    /// The file couldn't be parsed
+    Parse {
+        path: Option<PathBuf>,
+        error: Box<SpannedError>,
+        /// The line of the error, with a caret under the column
+        snippet: Option<String>,
+    },
+    /// The prefab couldn't be serialized
+    Serialize(ron::Error),
+    /// An entity has a parent that doesn't exist in the prefab
+    MissingParent { entity: usize, parent: usize },
+    /// The entity doesn't exist in the prefab
+    MissingEntity(usize),
+    /// The entity is its own ancestor
+    Cycle(usize),
+    /// The entity can't be reached from the root
+    Unreachable(usize),
+    /// The entity has no parent, but it isn't the root and the prefab isn't spawned as a forest
+    ExtraRoot(usize),
+    /// All the problems found by [`Prefab::validate`](crate::prefab::Prefab::validate)
+    Invalid(Vec<PrefabError>),
+    /// The prefab file is referenced by one of the prefabs it references
+    ReferenceCycle(PathBuf),
+    /// The path of an override doesn't lead to an entity of the base prefab
+    MissingPath(String),
+    /// The entity data can't be handled by the format
+    InvalidEntity { entity: usize, reason: String },
+    /// The root entity of the prefab doesn't exist in the world
+    MissingRoot(Entity),
+    /// The entity that should be the parent of the prefab doesn't exist in the world
+    MissingParentEntity(Entity),
+    /// No argument was given to the parameter, and it has no default value
+    MissingArg(String),
+    /// The sub-assets of the prefab couldn't be loaded, with the path of the failed assets
+    AssetLoad(Vec<String>),
+    /// The sub-assets of the entities couldn't be prepared, with the index of the entities
+    AssetPrepare(Vec<usize>),
+    /// Any other error given by a format
+    Custom(Box<dyn Error + Send + Sync>),
+}
+
+impl PrefabError {
+    /// Give the source of the prefab to the error, so it can point where the error is
+    pub fn with_source(self, source_path: &Path, bytes: &[u8]) -> Self {
+        match self {
+            Self::Io { path, error } => Self::Io {
+                path: path.or_else(|| Some(source_path.to_path_buf())),
+                error,
+            },
+            Self::Parse { path, error, .. } => {
+                let snippet = snippet(bytes, error.position);
+                Self::Parse {
+                    path: path.or_else(|| Some(source_path.to_path_buf())),
+                    error,
+                    snippet,
+                }
+            }
+            other => other,
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Io { path, .. } | Self::Parse { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+fn snippet(bytes: &[u8], position: Position) -> Option<String> {
+    if position.line == 0 {
+        return None;
+    }
+    let source = String::from_utf8_lossy(bytes);
+    let line = source.lines().nth(position.line - 1)?;
+    let number = position.line.to_string();
+    let padding = " ".repeat(number.len());
+    let caret = " ".repeat(position.col.saturating_sub(1));
+    Some(format!(
+        "{padding} |\n{number} | {line}\n{padding} | {caret}^"
+    ))
+}
+
+impl fmt::Display for PrefabError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, error } => match path {
+                Some(path) => write!(f, "Could not read {}: {error}", path.display()),
+                None => write!(f, "Could not read prefab: {error}"),
+            },
+            Self::Parse {
+                path,
+                error,
+                snippet,
+            } => {
+                // The errors converted from `ron::Error` have no position
+                let known = error.position.line != 0;
+                if let Some(path) = path {
+                    write!(f, "{}:", path.display())?;
+                    if !known {
+                        write!(f, " ")?;
+                    }
+                }
+                match known {
+                    true => write!(f, "{error}")?,
+                    false => write!(f, "{}", error.code)?,
+                }
+                if let Some(snippet) = snippet {
+                    write!(f, "\n{snippet}")?;
+                }
+                Ok(())
+            }
+            Self::Serialize(e) => write!(f, "Could not serialize prefab: {e}"),
+            Self::MissingParent { entity, parent } => write!(
+                f,
+                "Entity {entity} has the parent {parent}, that doesn't exist in the prefab"
+            ),
+            Self::MissingEntity(entity) => write!(f, "Entity {entity} not found in the prefab"),
+            Self::Cycle(entity) => write!(f, "Entity {entity} is its own ancestor"),
+            Self::Unreachable(entity) => {
+                write!(f, "Entity {entity} is not reachable from the root")
+            }
+            Self::ExtraRoot(entity) => {
+                write!(
+                    f,
+                    "Entity {entity} has no parent, only the root can, unless spawned as a forest"
+                )
+            }
+            Self::Invalid(errors) => {
+                write!(f, "The prefab is invalid:")?;
+                for error in errors {
+                    write!(f, "\n- {error}")?;
+                }
+                Ok(())
+            }
+            Self::ReferenceCycle(path) => {
+                write!(f, "The prefab {} references itself", path.display())
+            }
+            Self::MissingPath(path) => {
+                write!(f, "No entity in the path \"{path}\" of the base prefab")
+            }
+            Self::InvalidEntity { entity, reason } => write!(f, "Entity {entity}: {reason}"),
+            Self::MissingRoot(root) => write!(f, "The root {root:?} of the prefab doesn't exist"),
+            Self::MissingParentEntity(parent) => {
+                write!(f, "The parent {parent:?} of the prefab doesn't exist")
+            }
+            Self::MissingArg(name) => write!(f, "No argument given to the parameter ${name}"),
+            Self::AssetLoad(assets) => write!(f, "Could not load the assets {assets:?}"),
+            Self::AssetPrepare(entities) => {
+                write!(
+                    f,
+                    "Could not prepare the assets of the entities {entities:?}"
+                )
+            }
+            Self::Custom(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for PrefabError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { error, .. } => Some(error),
+            Self::Parse { error, .. } => Some(error.as_ref()),
+            Self::Serialize(e) => Some(e),
+            Self::Custom(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PrefabError {
+    fn from(value: io::Error) -> Self {
+        Self::Io {
+            path: None,
+            error: value,
+        }
+    }
+}
+
+impl From<SpannedError> for PrefabError {
+    fn from(value: SpannedError) -> Self {
+        Self::Parse {
+            path: None,
+            error: Box::new(value),
+            snippet: None,
+        }
+    }
+}
+
+/// Prefer [`SpannedError`] when possible, this error has no position in the file,
+/// so none is shown
+impl From<ron::Error> for PrefabError {
+    fn from(value: ron::Error) -> Self {
+        Self::Parse {
+            path: None,
+            error: Box::new(SpannedError {
+                code: value,
+                position: Position { line: 0, col: 0 },
+            }),
+            snippet: None,
+        }
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for PrefabError {
+    fn from(value: Box<dyn Error + Send + Sync>) -> Self {
+        Self::Custom(value)
+    }
+}
@@ -0,0 +1,347 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    args::ArgValue,
+    error::PrefabError,
+    prefab::{Format, FormatWriter, Prefab, PrefabData},
+};
+
+/// The file read by [`RonPrefabFormat`]
+///
+/// ```ron
+/// (
+///     data: Some(Value(1)),
+///     children: [
+///         // The name is spawned as a `Name`, and can be used to find the entity
+///         (name: Some("head"), data: Some(Value(2))),
+///         (
+///             data: Some(Value(3)),
+///             children: [(data: Some(Value(4)))],
+///         ),
+///     ],
+///     // Optional, other roots of the prefab, spawned as siblings of the first one
+///     roots: [(name: Some("lamp"), data: Some(Value(6)))],
+///     // Optional, added after the tree
+///     entities: [
+///         (parent: 1, data: Some(Value(5))),
+///         // Spawn the prefab of the file under this entity
+///         (parent: 2, prefab: Some("door.ron")),
+///     ],
+/// )
+/// ```
+///
+/// A variant of another prefab file gives its `base`, the root data is merged into the root of the base,
+/// and the children are added to it. The other entities of the base are changed by `overrides`:
+///
+/// ```ron
+/// (
+///     base: Some("goblin.ron"),
+///     overrides: [
+///         (entity: "head", data: Some(Value(8))),
+///         (entity: "0/2", children: [(data: Some(Value(9)))]),
+///     ],
+/// )
+/// ```
+///
+/// The parameters are declared with their default values, `None` when an argument is required.
+/// Any string `"$name"` in the data is replaced by the argument, see [`Prefab::apply_args`]:
+///
+/// ```ron
+/// (
+///     params: {"hp": Some(10), "label": None},
+///     data: Some((health: Some(Health("$hp")), label: Some(Label("$label")))),
+/// )
+/// ```
+#[derive(Deserialize, Serialize)]
+pub struct RonPrefab<PD> {
+    /// The parameters of the prefab, with their default values
+    #[serde(default = "HashMap::new", skip_serializing_if = "HashMap::is_empty")]
+    pub params: HashMap<String, Option<ArgValue>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Data of the root entity
+    #[serde(default = "Option::default")]
+    pub data: Option<PD>,
+    #[serde(default = "Vec::new")]
+    pub children: Vec<PrefabNode<PD>>,
+    /// Other roots of the prefab, that make it a forest
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<PrefabNode<PD>>,
+    /// Entities given by parent index.
+    ///
+    /// The root has index 0, the nodes of the tree follow in depth-first order,
+    /// then the other roots with their children, then the entities of this list.
+    #[serde(default = "Vec::new")]
+    pub entities: Vec<FlatPrefabEntity<PD>>,
+    /// A prefab file, relative to this one, that this prefab is a variant of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Changes to the entities of the base
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<PrefabOverride<PD>>,
+}
+
+/// An entity of the tree, with its children
+#[derive(Deserialize, Serialize)]
+pub struct PrefabNode<PD> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default = "Option::default")]
+    pub data: Option<PD>,
+    /// A prefab file, relative to this one, spawned under the entity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefab: Option<String>,
+    #[serde(default = "Vec::new")]
+    pub children: Vec<PrefabNode<PD>>,
+}
+
+/// An entity with the index of its parent
+#[derive(Deserialize, Serialize)]
+pub struct FlatPrefabEntity<PD> {
+    /// The root by default
+    #[serde(default)]
+    pub parent: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default = "Option::default")]
+    pub data: Option<PD>,
+    /// A prefab file, relative to this one, spawned under the entity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefab: Option<String>,
+}
+
+/// Changes to an entity of the base prefab
+#[derive(Deserialize, Serialize)]
+pub struct PrefabOverride<PD> {
+    /// The path of the entity from the root, with the names or the positions of the children,
+    /// `"1/0"` is the first child of the second child
+    pub entity: String,
+    /// Merged into the data of the entity
+    #[serde(default = "Option::default")]
+    pub data: Option<PD>,
+    /// Children added to the entity
+    #[serde(default = "Vec::new")]
+    pub children: Vec<PrefabNode<PD>>,
+}
+
+impl<PD> RonPrefab<PD> {
+    pub fn into_prefab(self) -> Result<Prefab<PD>, PrefabError>
+    where
+        PD: Serialize + DeserializeOwned,
+    {
+        let mut prefab = Prefab::from_data(self.data);
+        if let Some(name) = self.name {
+            prefab.get_entity_mut(0).unwrap().set_name(name);
+        }
+        for (name, default) in self.params {
+            prefab.declare_param(name, default);
+        }
+        for child in self.children {
+            node_tree(child, 0, &mut prefab);
+        }
+        for root in self.roots {
+            node_root(root, &mut prefab);
+        }
+
+        let first_flat = prefab.len();
+        let total = first_flat + self.entities.len();
+        for (i, entity) in self.entities.into_iter().enumerate() {
+            if entity.parent >= total {
+                return Err(PrefabError::MissingParent {
+                    entity: first_flat + i,
+                    parent: entity.parent,
+                });
+            }
+            let index = prefab.add(Some(entity.parent), entity.data);
+            set_entity(&mut prefab, index, entity.name, entity.prefab);
+        }
+
+        if let Some(base) = self.base {
+            prefab.set_base(base);
+        }
+        for change in self.overrides {
+            let index = prefab.add_override(change.entity, change.data);
+            for child in change.children {
+                node_tree(child, index, &mut prefab);
+            }
+        }
+        Ok(prefab)
+    }
+}
+
+fn node_tree<PD>(node: PrefabNode<PD>, parent: usize, prefab: &mut Prefab<PD>) {
+    let index = prefab.add(Some(parent), node.data);
+    set_entity(prefab, index, node.name, node.prefab);
+    for child in node.children {
+        node_tree(child, index, prefab);
+    }
+}
+
+fn node_root<PD>(node: PrefabNode<PD>, prefab: &mut Prefab<PD>) {
+    let index = prefab.add(None, node.data);
+    set_entity(prefab, index, node.name, node.prefab);
+    for child in node.children {
+        node_tree(child, index, prefab);
+    }
+}
+
+fn set_entity<PD>(
+    prefab: &mut Prefab<PD>,
+    index: usize,
+    name: Option<String>,
+    reference: Option<String>,
+) {
+    let entidade = prefab.get_entity_mut(index).unwrap();
+    if let Some(name) = name {
+        entidade.set_name(name);
+    }
+    if let Some(reference) = reference {
+        entidade.set_reference(reference);
+    }
+}
+
+/// Deserialize RON bytes, keeping the position of the error in the file
+pub fn from_ron_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, PrefabError> {
+    let mut de = ron::de::Deserializer::from_bytes(bytes)?;
+    let valor = T::deserialize(&mut de).map_err(|e| de.span_error(e))?;
+    Ok(valor)
+}
+
+/// A [`Format`] for any deserializable PrefabData, reading a [`RonPrefab`] file
+pub struct RonPrefabFormat<PD: PrefabData>(PhantomData<PD>);
+
+impl<PD> Format<PD> for RonPrefabFormat<PD>
+where
+    PD: PrefabData + Serialize + for<'a> Deserialize<'a>,
+{
+    fn load_from_bytes(bytes: Vec<u8>) -> Result<Prefab<PD>, PrefabError> {
+        let valor: RonPrefab<PD> = from_ron_bytes(&bytes)?;
+        valor.into_prefab()
+    }
+}
+
+impl<PD> FormatWriter<PD> for RonPrefabFormat<PD>
+where
+    PD: PrefabData + Serialize,
+{
+    fn save_to_bytes(prefab: &Prefab<PD>) -> Result<Vec<u8>, PrefabError> {
+        let children = prefab.all_parents_childs();
+        let mut visited = vec![false; prefab.len()];
+        let root = node_ref(prefab, &children, 0, &mut visited)?;
+        let mut roots = Vec::new();
+        for index in prefab.roots().filter(|index| *index != 0) {
+            let node = node_ref(prefab, &children, index, &mut visited)?;
+            if node.target.is_some() {
+                return Err(PrefabError::InvalidEntity {
+                    entity: index,
+                    reason: "Only the children of the root can override the base".to_string(),
+                });
+            }
+            roots.push(node);
+        }
+        if let Some(index) = visited.iter().position(|v| !v) {
+            return Err(PrefabError::Unreachable(index));
+        }
+        if root.prefab.is_some() {
+            return Err(PrefabError::InvalidEntity {
+                entity: 0,
+                reason: "The root can't reference a prefab".to_string(),
+            });
+        }
+
+        // The overrides are children of the root in the prefab
+        let (overrides, children): (Vec<_>, Vec<_>) =
+            root.children.into_iter().partition(|c| c.target.is_some());
+        let valor = RonPrefabRef {
+            params: prefab.params(),
+            name: root.name,
+            data: root.data,
+            children,
+            roots,
+            base: prefab.get_base(),
+            overrides: overrides
+                .into_iter()
+                .map(|node| PrefabOverrideRef {
+                    entity: node.target.unwrap_or_default(),
+                    data: node.data,
+                    children: node.children,
+                })
+                .collect(),
+        };
+        let ron = ron::ser::to_string_pretty(&valor, ron::ser::PrettyConfig::default())
+            .map_err(PrefabError::Serialize)?;
+        Ok(ron.into_bytes())
+    }
+}
+
+// Borrowed versions of the schema, so the data doesn't need to be cloned when writing
+#[derive(Serialize)]
+struct RonPrefabRef<'a, PD> {
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    params: &'a HashMap<String, Option<ArgValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    data: Option<&'a PD>,
+    children: Vec<PrefabNodeRef<'a, PD>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    roots: Vec<PrefabNodeRef<'a, PD>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    overrides: Vec<PrefabOverrideRef<'a, PD>>,
+}
+
+#[derive(Serialize)]
+struct PrefabNodeRef<'a, PD> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    data: Option<&'a PD>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefab: Option<&'a str>,
+    children: Vec<PrefabNodeRef<'a, PD>>,
+    #[serde(skip)]
+    target: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct PrefabOverrideRef<'a, PD> {
+    entity: &'a str,
+    data: Option<&'a PD>,
+    children: Vec<PrefabNodeRef<'a, PD>>,
+}
+
+fn node_ref<'a, PD>(
+    prefab: &'a Prefab<PD>,
+    children: &HashMap<usize, Vec<usize>>,
+    index: usize,
+    visited: &mut [bool],
+) -> Result<PrefabNodeRef<'a, PD>, PrefabError> {
+    let Some(entity) = prefab.get_entity(index) else {
+        return Err(PrefabError::MissingEntity(index));
+    };
+    if visited[index] {
+        return Err(PrefabError::Cycle(index));
+    }
+    visited[index] = true;
+
+    let mut nodes = Vec::new();
+    for child in children.get(&index).into_iter().flatten() {
+        let node = node_ref(prefab, children, *child, visited)?;
+        if index != 0 && node.target.is_some() {
+            return Err(PrefabError::InvalidEntity {
+                entity: *child,
+                reason: "Only the children of the root can override the base".to_string(),
+            });
+        }
+        nodes.push(node);
+    }
+    Ok(PrefabNodeRef {
+        name: entity.get_name(),
+        data: entity.get_data(),
+        prefab: entity.get_reference(),
+        children: nodes,
+        target: entity.get_target(),
+    })
+}
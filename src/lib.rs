@@ -1,7 +1,15 @@
+pub mod args;
+pub mod asset;
 pub mod components;
+pub mod error;
+pub mod format;
 pub mod prefab;
 pub mod ui;
 
+pub use args::*;
+pub use asset::*;
 pub use bevy_prfb_macro::*;
 pub use components::*;
+pub use error::*;
+pub use format::*;
 pub use prefab::*;
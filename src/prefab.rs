@@ -1,524 +1,2923 @@
 use std::{
-    collections::HashMap,
-    error::Error,
+    collections::{HashMap, VecDeque},
     fs,
     io::{self, Read},
     marker::PhantomData,
-    path::Path,
+    path::{Component as PathComponent, Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
 };
 
 use bevy::{
+    asset::{AssetServer, RecursiveDependencyLoadState, UntypedHandle},
     ecs::{
         component::Component,
-        system::Command,
-        world::{EntityWorldMut, World},
+        event::{EventWriter, Events},
+        removal_detection::RemovedComponents,
+        system::{Command, EntityCommands, Query, Res, ResMut},
+        world::{EntityRef, EntityWorldMut, World},
+    },
+    hierarchy::{
+        despawn_with_children_recursive, BuildWorldChildren, Children, DespawnRecursiveExt, Parent,
     },
-    hierarchy::BuildWorldChildren,
     log::{error, warn},
-    prelude::{App, Bundle, Commands, Entity, Event, Plugin, Resource},
+    prelude::{App, Bundle, Commands, Entity, Event, Last, Name, Plugin, Resource, Update},
+    time::{Time, Timer, TimerMode},
+    transform::{components::Transform, TransformBundle},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize, Serializer};
+
+use crate::{
+    args::{substitute, ArgValue, PrefabArgs},
+    error::PrefabError,
 };
 
-pub struct Prefab<T> {
+/// State of a [`Prefab`] which assets weren't loaded yet, it must be prepared before spawning
+pub struct Unprepared;
+
+/// State of a [`Prefab`] with all the assets loaded, given by [`Prefab::prepare_entities`]
+pub struct Prepared;
+
+/// The prepared prefab, or the prefab given back with the reason it couldn't be prepared
+pub type PrepareResult<T> = Result<Prefab<T, Prepared>, (Box<Prefab<T>>, PrefabError)>;
+
+/// The entities of a prefab, only a [`Prepared`] prefab can be spawned
+pub struct Prefab<T, S = Unprepared> {
     entidades: Vec<PrefabEntityBuilder<T>>,
+    base: Option<String>,
+    source: Option<String>,
+    /// The declared parameters, with their default values
+    params: HashMap<String, Option<ArgValue>>,
+    /// [`Prefab::apply_args`], kept with the parameters so their defaults are applied when preparing
+    substitute: Option<SubstituteArgs<T>>,
+    state: PhantomData<S>,
 }
+
+type SubstituteArgs<T> = fn(&mut Prefab<T>, &PrefabArgs) -> Result<(), PrefabError>;
 impl<T> Prefab<T> {
     pub fn new() -> Self {
         Self {
             entidades: vec![PrefabEntityBuilder::default()],
+            base: None,
+            source: None,
+            params: HashMap::new(),
+            substitute: None,
+            state: PhantomData,
         }
     }
 
     pub fn from_data(data: Option<T>) -> Self {
         Self {
             entidades: vec![PrefabEntityBuilder::new(None, data)],
+            base: None,
+            source: None,
+            params: HashMap::new(),
+            substitute: None,
+            state: PhantomData,
         }
     }
 
-    pub fn add(&mut self, parent: Option<usize>, data: Option<T>) -> usize {
-        let index = self.entidades.len();
-        self.entidades.push(PrefabEntityBuilder::new(parent, data));
+    /// Make this prefab a variant of the given prefab file.
+    ///
+    /// When loaded, the data of the root is merged into the root of the base, and the children are added to it.
+    /// Use [`Prefab::add_override`] to change the other entities of the base
+    pub fn set_base(&mut self, path: impl Into<String>) {
+        self.base = Some(path.into());
+    }
+
+    pub fn take_base(&mut self) -> Option<String> {
+        self.base.take()
+    }
+
+    /// Add an entity that changes the entity of the base in the given path,
+    /// its data is merged into the base entity and its children are added to it.
+    ///
+    /// The path is given from the root, see [`Prefab::index_by_path`]
+    pub fn add_override(&mut self, path: impl Into<String>, data: Option<T>) -> usize {
+        let index = self.add(Some(0), data);
+        self.entidades[index].target = Some(path.into());
         index
     }
 
-    /// Return a Hash for accessing the entities with parent relation
-    pub fn all_parents_childs(&self) -> HashMap<usize, Vec<usize>> {
-        let mut hash: HashMap<usize, Vec<usize>> = HashMap::new();
-        for (index, entidade) in self.entidades.iter().enumerate() {
-            if let Some(parent) = entidade.parent {
-                if let Some(vec) = hash.get_mut(&parent) {
-                    vec.push(index);
-                } else {
-                    hash.insert(parent, vec![index]);
+    /// Apply the variant over this prefab, see [`Prefab::set_base`].
+    /// The variant must already have its entities in the base, the base itself isn't loaded
+    pub fn apply_variant(mut self, variant: Prefab<T>) -> Result<Prefab<T>, PrefabError>
+    where
+        T: PrefabData,
+    {
+        let children = variant.all_parents_childs();
+        // Every root of the variant, reversed so they keep their order
+        let mut pilha: Vec<_> = variant.roots().collect();
+        pilha.reverse();
+        let mut entidades: Vec<_> = variant.entidades.into_iter().map(Some).collect();
+        // Index of the variant entities in the result
+        let mut indices = vec![None; entidades.len()];
+        // The data is merged once every index is known, so its references can be remapped
+        let mut datas = Vec::new();
+        while let Some(index) = pilha.pop() {
+            let Some(mut entidade) = entidades.get_mut(index).and_then(Option::take) else {
+                return Err(PrefabError::MissingEntity(index));
+            };
+            let novo = match (index, entidade.target.take()) {
+                (0, _) => 0,
+                (_, Some(path)) => self
+                    .index_by_path(&path)
+                    .ok_or(PrefabError::MissingPath(path))?,
+                // The other roots of the variant are also roots
+                _ => {
+                    let parent = entidade.parent.map(|p| indices[p].unwrap_or(0));
+                    self.add(parent, None)
                 }
+            };
+            indices[index] = Some(novo);
+
+            if let Some(data) = entidade.take_data() {
+                datas.push((novo, data));
+            }
+            let alvo = &mut self.entidades[novo];
+            if let Some(reference) = entidade.take_reference() {
+                alvo.set_reference(reference);
             }
-            hash.insert(index, vec![]);
+            if let Some(name) = entidade.name.take() {
+                alvo.set_name(name);
+            }
+            // Reversed, so the children keep their order
+            pilha.extend(children.get(&index).into_iter().flatten().rev());
         }
-        hash
+        if let Some(index) = entidades.iter().position(Option::is_some) {
+            return Err(PrefabError::Unreachable(index));
+        }
+        for (novo, mut other) in datas {
+            other.remap_refs(&|index| indices.get(index).copied().flatten());
+            let alvo = &mut self.entidades[novo];
+            match alvo.data.as_mut() {
+                Some(data) => data.merge(other),
+                None => alvo.data = Some(other),
+            }
+        }
+        // The defaults of the variant replace the ones of the base
+        self.params.extend(variant.params);
+        self.substitute = self.substitute.or(variant.substitute);
+        Ok(self)
     }
 
-    pub fn get_entity(&self, index: usize) -> Option<&PrefabEntityBuilder<T>> {
-        self.entidades.get(index)
+    pub fn add(&mut self, parent: Option<usize>, data: Option<T>) -> usize {
+        let index = self.entidades.len();
+        self.entidades.push(PrefabEntityBuilder::new(parent, data));
+        index
     }
 
+    /// Only an unprepared prefab can be changed, the assets of the new data wouldn't be loaded
     pub fn get_entity_mut(&mut self, index: usize) -> Option<&mut PrefabEntityBuilder<T>> {
         self.entidades.get_mut(index)
     }
 
-    /// Function must be used only after prepared
-    pub fn spawn(
-        &mut self,
-        processed_children: &HashMap<usize, Vec<usize>>,
-        init: &usize,
-        world: &mut World,
-    ) -> Option<Entity>
+    pub fn get_entity_by_name_mut(&mut self, name: &str) -> Option<&mut PrefabEntityBuilder<T>> {
+        let index = self.index_by_name(name)?;
+        self.entidades.get_mut(index)
+    }
+
+    /// See [`Prefab::index_by_path`]
+    pub fn get_entity_by_path_mut(&mut self, path: &str) -> Option<&mut PrefabEntityBuilder<T>> {
+        let index = self.index_by_path(path)?;
+        self.entidades.get_mut(index)
+    }
+
+    /// Add an entity that will receive the prefab of the given file, relative to the file of this prefab.
+    /// The reference is resolved when the prefab is loaded, see [`PrefabLoader::resolve_references`]
+    pub fn add_reference(&mut self, parent: Option<usize>, path: impl Into<String>) -> usize {
+        let index = self.add(parent, None);
+        self.entidades[index].set_reference(path);
+        index
+    }
+
+    /// Append all the entities of the other prefab, with its root as a child of the given entity.
+    /// Returns the new index of the other root
+    pub fn graft(&mut self, parent: usize, other: Prefab<T>) -> usize
     where
         T: PrefabData,
     {
-        let mut root_entity = world.spawn_empty();
-        if let Some(c) = processed_children.get(init) {
-            if let Some(parent) = self.entidades.get_mut(*init) {
-                if let Some(data) = parent.take_data() {
-                    data.insert_into_entity(&mut root_entity);
-                }
-                root_entity.insert(PrefabEntity::<T>(PhantomData::default()));
-            }
-            for child in c {
-                // Safety: Its safe because the location is updated after the operation.
-                unsafe {
-                    let child = self.spawn(processed_children, child, root_entity.world_mut());
-                    root_entity.update_location();
-                    if let Some(child) = child {
-                        root_entity.add_child(child);
-                    }
-                }
-            }
-        } else {
-            // Probably an error with wrong adding the children.
-            warn!(target: "prefab", "[warning] Entity not found in the prefab with index {}", init);
-            return None;
-        }
-        Some(root_entity.id())
+        self.append(parent, other)
     }
 
-    // The first entity will have your specified root
-    fn spawn_with_root(
-        &mut self,
-        processed_children: &HashMap<usize, Vec<usize>>,
-        init: &usize,
-        world: &mut World,
-        root: Entity,
-    ) -> Option<Entity>
+    /// Declare a parameter of the prefab, written as `"$name"` in the data of the entities.
+    /// Without a default, an argument must be given to it, see [`Prefab::apply_args`].
+    /// The parameters without arguments receive their defaults when the prefab is prepared
+    pub fn declare_param(&mut self, name: impl Into<String>, default: Option<ArgValue>)
     where
-        T: PrefabData,
+        T: Serialize + DeserializeOwned,
     {
-        //Entity must exist
-        let mut root = world.entity_mut(root);
-        if let Some(c) = processed_children.get(init) {
-            if let Some(parent) = self.entidades.get_mut(*init) {
-                if let Some(data) = parent.take_data() {
-                    data.insert_into_entity(&mut root);
-                }
+        self.params.insert(name.into(), default);
+        self.substitute = Some(Self::apply_args);
+    }
+
+    /// The declared parameters, with their default values
+    pub fn params(&self) -> &HashMap<String, Option<ArgValue>> {
+        &self.params
+    }
+
+    /// Replace every `"$name"` in the data of the entities by the argument, or the default of the parameter.
+    /// Only the declared parameters are replaced, `"$$name"` keeps the string `"$name"` of a parameter.
+    /// Nothing is changed if an argument is missing or has the wrong type
+    pub fn apply_args(&mut self, args: &PrefabArgs) -> Result<(), PrefabError>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let lookup = |name: &str| {
+            self.params.get(name).map(|default| {
+                args.get(name)
+                    .or(default.as_ref())
+                    .map(|valor| valor.value().clone())
+            })
+        };
+        let mut novos = Vec::with_capacity(self.entidades.len());
+        for (index, entidade) in self.entidades.iter().enumerate() {
+            let data = entidade.get_data();
+            novos.push(
+                data.map(|data| substitute(index, data, &lookup))
+                    .transpose()?,
+            );
+        }
+        for (entidade, novo) in self.entidades.iter_mut().zip(novos) {
+            if let Some(novo) = novo {
+                entidade.data = Some(novo);
             }
-            for child in c {
-                // Safety: Its safe because the location is updated after the operation.
-                unsafe {
-                    let child = self.spawn(processed_children, child, root.world_mut());
-                    root.update_location();
-                    if let Some(child) = child {
-                        root.add_child(child);
-                    }
-                }
+        }
+        self.params.clear();
+        self.substitute = None;
+        Ok(())
+    }
+
+    /// Convert the data of every entity, keeping the hierarchy
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Prefab<U> {
+        self.filter_map(|data| Some(f(data)))
+    }
+
+    /// Convert the data of every entity, the entities are kept without data when None is returned,
+    /// so the hierarchy doesn't change
+    pub fn filter_map<U>(self, mut f: impl FnMut(T) -> Option<U>) -> Prefab<U> {
+        let entidades = self
+            .entidades
+            .into_iter()
+            .map(|entidade| PrefabEntityBuilder {
+                parent: entidade.parent,
+                data: entidade.data.and_then(&mut f),
+                reference: entidade.reference,
+                target: entidade.target,
+                name: entidade.name,
+            })
+            .collect();
+        Prefab {
+            entidades,
+            base: self.base,
+            source: self.source,
+            params: self.params,
+            // The arguments can't be applied to the new data
+            substitute: None,
+            state: PhantomData,
+        }
+    }
+
+    /// Take the references of all the entities, with the index of the entity
+    pub fn take_references(&mut self) -> Vec<(usize, String)> {
+        let mut references = Vec::new();
+        for (index, entidade) in self.entidades.iter_mut().enumerate() {
+            if let Some(reference) = entidade.take_reference() {
+                references.push((index, reference));
             }
-        } else {
-            // Probably an error with wrong adding the children.
-            warn!(target: "prefab", "[warning] Entity not found in the prefab with index {}", init);
-            return None;
         }
-        Some(root.id())
+        references
+    }
+
+    /// Build a prefab from an entity and all its descendants, the counterpart of [`Prefab::spawn`].
+    /// Returns None if the root doesn't exist
+    pub fn capture(world: &World, root: Entity) -> Option<Self>
+    where
+        T: ExtractPrefabData,
+    {
+        let root = world.get_entity(root)?;
+        let mut prefab = Self::from_data(T::extract_from_entity(&root));
+        prefab.entidades[0].name = root.get::<Name>().map(|n| n.to_string());
+        prefab.capture_children(world, &root, 0);
+        Some(prefab)
+    }
+
+    fn capture_children(&mut self, world: &World, entity: &EntityRef, index: usize)
+    where
+        T: ExtractPrefabData,
+    {
+        let Some(children) = entity.get::<Children>() else {
+            return;
+        };
+        for child in children.iter() {
+            let Some(child) = world.get_entity(*child) else {
+                continue;
+            };
+            let child_index = self.add(Some(index), T::extract_from_entity(&child));
+            self.entidades[child_index].name = child.get::<Name>().map(|n| n.to_string());
+            self.capture_children(world, &child, child_index);
+        }
+    }
+
+    /// This function will load all the assets, so the prefab can be spawned.
+    /// If you want to be performatic, store the prepared prefab and just borrow then afterwards.
+    /// Fails if the sub-assets of any entity couldn't be prepared
+    pub fn prepare_entities(self, world: &mut World) -> Result<Prefab<T, Prepared>, PrefabError>
+    where
+        T: PrefabData,
+    {
+        self.try_prepare(world).map_err(|(_, error)| error)
     }
 
-    /// This function will load all the assets
-    /// If you want to be performatic, store the assets and just borrow then afterwards
-    pub fn prepare_entities(&mut self, world: &mut World) -> bool
+    /// Like [`Prefab::prepare_entities`], but the prefab is given back when the sub-assets of
+    /// any entity couldn't be prepared, so it can be prepared again
+    pub fn try_prepare(mut self, world: &mut World) -> PrepareResult<T>
     where
         T: PrefabData,
     {
-        let mut loaded = false;
-        for entidade in self.entidades.iter_mut() {
-            loaded |= entidade.prepare_data(world)
+        // The parameters without arguments receive their defaults
+        if !self.params.is_empty() {
+            let applied = match self.substitute {
+                Some(substitute) => substitute(&mut self, &PrefabArgs::new()),
+                None => Err(PrefabError::MissingArg(
+                    self.params.keys().min().cloned().unwrap_or_default(),
+                )),
+            };
+            if let Err(error) = applied {
+                return Err((Box::new(self), error));
+            }
         }
-        loaded
+        let failed: Vec<_> = self
+            .entidades
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, entidade)| entidade.prepare_data(world).then_some(index))
+            .collect();
+        if !failed.is_empty() {
+            return Err((Box::new(self), PrefabError::AssetPrepare(failed)));
+        }
+        Ok(Prefab {
+            entidades: self.entidades,
+            base: self.base,
+            source: self.source,
+            params: self.params,
+            substitute: self.substitute,
+            state: PhantomData,
+        })
     }
 }
-impl<T> Default for Prefab<T> {
-    fn default() -> Self {
-        Self::new()
+impl<T, S> Prefab<T, S> {
+    /// Where the prefab came from, like the path of its file, given to the spawned [`PrefabInstance`]
+    pub fn get_source(&self) -> Option<&str> {
+        self.source.as_deref()
     }
-}
-impl<T> Clone for Prefab<T>
-where
-    T: Clone,
-{
-    fn clone(&self) -> Self {
-        Self {
-            entidades: self.entidades.clone(),
+
+    pub fn set_source(&mut self, source: impl Into<String>) {
+        self.source = Some(source.into());
+    }
+
+    /// The prefab file this prefab is a variant of, relative to the file of this prefab
+    pub fn get_base(&self) -> Option<&str> {
+        self.base.as_deref()
+    }
+
+    /// Find the entity in the path from the root, each part is the name of the child,
+    /// or its position: `"panel/title"`, `"1/0"` is the first child of the second child.
+    /// An empty path is the root
+    pub fn index_by_path(&self, path: &str) -> Option<usize> {
+        let children = self.all_parents_childs();
+        let mut index = 0;
+        for parte in path.split('/').filter(|p| !p.is_empty()) {
+            let filhos = children.get(&index)?;
+            index = match filhos
+                .iter()
+                .find(|c| self.entidades[**c].get_name() == Some(parte))
+            {
+                Some(filho) => *filho,
+                None => *filhos.get(parte.parse::<usize>().ok()?)?,
+            };
         }
+        Some(index)
     }
-}
 
-pub struct PrefabEntityBuilder<T> {
-    parent: Option<usize>,
-    data: Option<T>,
-}
-impl<T> PrefabEntityBuilder<T> {
-    pub fn new(parent: Option<usize>, data: Option<T>) -> Self {
-        Self { parent, data }
+    /// Index of the first entity with the name
+    pub fn index_by_name(&self, name: &str) -> Option<usize> {
+        self.entidades
+            .iter()
+            .position(|e| e.get_name() == Some(name))
     }
 
-    pub fn get_data_or_insert_with(&mut self, f: impl FnOnce() -> T) -> &mut T {
-        self.data.get_or_insert_with(f)
+    pub fn get_entity_by_name(&self, name: &str) -> Option<&PrefabEntityBuilder<T>> {
+        self.entidades.get(self.index_by_name(name)?)
     }
 
-    pub fn get_data_or_default(&mut self) -> &mut T
-    where
-        T: Default,
-    {
-        self.data.get_or_insert_with(T::default)
+    /// See [`Prefab::index_by_path`]
+    pub fn get_entity_by_path(&self, path: &str) -> Option<&PrefabEntityBuilder<T>> {
+        self.entidades.get(self.index_by_path(path)?)
     }
 
-    pub fn get_data(&self) -> Option<&T> {
-        self.data.as_ref()
+    /// If any entity still references another prefab
+    pub fn has_references(&self) -> bool {
+        self.entidades.iter().any(|e| e.reference.is_some())
     }
 
-    pub fn get_data_mut(&mut self) -> Option<&mut T> {
-        self.data.as_mut()
+    /// Return a Hash for accessing the entities with parent relation
+    pub fn all_parents_childs(&self) -> HashMap<usize, Vec<usize>> {
+        let mut hash: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (index, entidade) in self.entidades.iter().enumerate() {
+            // The parent may come after its children, so its children are kept
+            hash.entry(index).or_default();
+            if let Some(parent) = entidade.parent {
+                hash.entry(parent).or_default().push(index);
+            }
+        }
+        hash
     }
 
-    pub fn set_parent(&mut self, parent: usize) {
-        self.parent = Some(parent);
+    /// The entities without a parent, the root and the other roots of a forest
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.entidades
+            .iter()
+            .enumerate()
+            .filter(|(_, entidade)| entidade.parent.is_none())
+            .map(|(index, _)| index)
     }
 
-    pub fn set_data(&mut self, data: T) {
-        self.data = Some(data);
+    /// If the prefab has more than one root, see [`PrefabCommandsExt::spawn_prefab_forest`]
+    pub fn is_forest(&self) -> bool {
+        self.roots().nth(1).is_some()
     }
 
-    pub fn take_data(&mut self) -> Option<T> {
-        self.data.take()
+    /// Find all the problems of the hierarchy that keep entities from being spawned:
+    /// parents out of the prefab, cycles, entities that can't be reached from the root
+    /// and entities other than the root without a parent.
+    ///
+    /// Run by the spawning commands in debug builds
+    pub fn validate(&self) -> Result<(), Vec<PrefabError>> {
+        self.check_hierarchy(false)
     }
 
-    pub fn prepare_data(&mut self, world: &mut World) -> bool
-    where
-        T: PrefabData,
-    {
-        if let Some(data) = self.get_data_mut() {
-            data.load_sub_assets(world)
+    /// Like [`Prefab::validate`], but any entity can be a root of the forest.
+    ///
+    /// Run by the loaders and the forest commands in debug builds
+    pub fn validate_forest(&self) -> Result<(), Vec<PrefabError>> {
+        self.check_hierarchy(true)
+    }
+
+    fn check_hierarchy(&self, forest: bool) -> Result<(), Vec<PrefabError>> {
+        if self.entidades.is_empty() {
+            return Err(vec![PrefabError::MissingEntity(0)]);
+        }
+        let mut errors = Vec::new();
+        // The entities with a problem of their own, not reported again as unreachable
+        let mut reported = vec![false; self.entidades.len()];
+        for (index, entidade) in self.entidades.iter().enumerate() {
+            match (index, entidade.parent) {
+                (0, Some(_)) => errors.push(PrefabError::InvalidEntity {
+                    entity: 0,
+                    reason: "The root can't have a parent".to_string(),
+                }),
+                (0, None) => continue,
+                (_, None) if forest => continue,
+                (_, None) => errors.push(PrefabError::ExtraRoot(index)),
+                (_, Some(parent)) if parent >= self.entidades.len() => {
+                    errors.push(PrefabError::MissingParent {
+                        entity: index,
+                        parent,
+                    })
+                }
+                _ => continue,
+            }
+            reported[index] = true;
+        }
+
+        // Follow the parents of each entity, until an entity already visited
+        let mut estado = vec![0u8; self.entidades.len()];
+        for inicio in 0..self.entidades.len() {
+            let mut caminho = Vec::new();
+            let mut atual = Some(inicio);
+            while let Some(index) = atual.filter(|i| *i < self.entidades.len()) {
+                match estado[index] {
+                    // Visited in this path, the rest of the path from it is a cycle
+                    1 => {
+                        let ciclo = &caminho[caminho.iter().position(|i| *i == index).unwrap()..];
+                        for i in ciclo {
+                            reported[*i] = true;
+                        }
+                        errors.push(PrefabError::Cycle(*ciclo.iter().min().unwrap()));
+                        break;
+                    }
+                    2 => break,
+                    _ => {}
+                }
+                estado[index] = 1;
+                caminho.push(index);
+                atual = self.entidades[index].parent;
+            }
+            for index in caminho {
+                estado[index] = 2;
+            }
+        }
+
+        let children = self.all_parents_childs();
+        let mut visited = vec![false; self.entidades.len()];
+        let mut pilha = if forest {
+            self.roots().collect()
         } else {
-            false
+            vec![0]
+        };
+        while let Some(index) = pilha.pop() {
+            if std::mem::replace(&mut visited[index], true) {
+                continue;
+            }
+            pilha.extend(children.get(&index).into_iter().flatten());
+        }
+        for (index, visited) in visited.into_iter().enumerate() {
+            if !visited && !reported[index] {
+                errors.push(PrefabError::Unreachable(index));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
         }
     }
-}
-impl<T> Default for PrefabEntityBuilder<T> {
-    fn default() -> Self {
-        Self {
-            parent: None,
-            data: None,
+
+    // Validate only in debug builds, with all the problems in one error
+    pub(crate) fn debug_validate(&self) -> Result<(), PrefabError> {
+        if cfg!(debug_assertions) {
+            self.validate().map_err(PrefabError::Invalid)?;
         }
+        Ok(())
     }
-}
-impl<T> Clone for PrefabEntityBuilder<T>
-where
-    T: Clone,
-{
-    fn clone(&self) -> Self {
-        Self {
-            parent: self.parent.clone(),
-            data: self.data.clone(),
+
+    pub(crate) fn debug_validate_forest(&self) -> Result<(), PrefabError> {
+        if cfg!(debug_assertions) {
+            self.validate_forest().map_err(PrefabError::Invalid)?;
         }
+        Ok(())
     }
-}
 
-pub trait PrefabData: Send + Sync + 'static {
-    /// Transform self into a component and insert into an entity
-    fn insert_into_entity(self, entidade: &mut EntityWorldMut);
-    fn load_sub_assets(&mut self, _world: &mut World) -> bool {
-        false
+    /// Number of entities in the prefab
+    pub fn len(&self) -> usize {
+        self.entidades.len()
     }
-}
 
-/// Suport trait for simple data that can be easily converted to a Component
-/// PrefabData is implemented for those who implement this trait + Clone
-/// Mustn't implement this trait the Components based on assets loading.
-pub trait IntoComponent {
-    type Component: Component;
-    fn into_component(self) -> Self::Component;
-}
+    pub fn is_empty(&self) -> bool {
+        self.entidades.is_empty()
+    }
 
-/// The formatter used for deserialize purposes
-pub trait Format<PD>
-where
-    Self: Send + Sync,
-{
-    fn load_from_bytes(bytes: Vec<u8>) -> Result<Prefab<PD>, Box<dyn Error>>;
-}
+    pub fn get_entity(&self, index: usize) -> Option<&PrefabEntityBuilder<T>> {
+        self.entidades.get(index)
+    }
 
-#[derive(Resource, Default)]
-/// Helper to some prefab functions
-pub struct PrefabLoader;
+    /// The parent of the entity, None for the root or if the entity doesn't exist
+    pub fn parent_of(&self, index: usize) -> Option<usize> {
+        self.entidades.get(index)?.parent
+    }
 
-impl PrefabLoader {
-    pub fn create_prefab<T: PrefabData, F: Format<T>, S: AsRef<Path>>(
-        nome: S,
-    ) -> Result<Prefab<T>, Box<dyn Error>> {
-        let prefab = F::load_from_bytes(Self::load_prefab(nome)?)?;
-        Ok(prefab)
+    /// The children of the entity, in the order they were added
+    pub fn children_of(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.entidades
+            .iter()
+            .enumerate()
+            .filter(move |(_, entidade)| entidade.parent == Some(index))
+            .map(|(filho, _)| filho)
     }
 
-    /// Instead of spawning the prefab, just create the prefab
-    /// - T: The prefab data that all the entities are bound for.
-    /// - F: The formatter for loading the prefab.
-    /// - S: The relative path of the prefab
-    // pub fn lazy_create<T: PrefabData, F: Format<T>, S: AsRef<Path>>(&self, world: &mut World, nome: S) -> Result<Prefab<T>, Box<dyn Error>>{
-    //     let mut prefab = F::load_from_bytes(Self::load_prefab(nome)?)?;
+    /// The entity and all its descendants, each entity before its children
+    pub fn depth_first(&self, index: usize) -> impl Iterator<Item = usize> {
+        let children = self.all_parents_childs();
+        let mut visited = vec![false; self.entidades.len()];
+        let mut ordem = Vec::new();
+        let mut pilha: Vec<_> = (index < self.entidades.len())
+            .then_some(index)
+            .into_iter()
+            .collect();
+        while let Some(atual) = pilha.pop() {
+            // Cycles are visited only once
+            if std::mem::replace(&mut visited[atual], true) {
+                continue;
+            }
+            ordem.push(atual);
+            pilha.extend(children.get(&atual).into_iter().flatten().rev());
+        }
+        ordem.into_iter()
+    }
 
-    //     prefab.prepare_entities(world);
-    //     Ok(prefab)
-    // }
+    /// The entity and all its descendants, level by level
+    pub fn breadth_first(&self, index: usize) -> impl Iterator<Item = usize> {
+        let children = self.all_parents_childs();
+        let mut visited = vec![false; self.entidades.len()];
+        let mut ordem = Vec::new();
+        let mut fila: VecDeque<_> = (index < self.entidades.len())
+            .then_some(index)
+            .into_iter()
+            .collect();
+        while let Some(atual) = fila.pop_front() {
+            if std::mem::replace(&mut visited[atual], true) {
+                continue;
+            }
+            ordem.push(atual);
+            fila.extend(children.get(&atual).into_iter().flatten());
+        }
+        ordem.into_iter()
+    }
 
-    fn load_prefab<S: AsRef<Path>>(path: S) -> Result<Vec<u8>, io::Error> {
-        let path = path.as_ref();
+    /// Move the entity, with its descendants, to the children of the new parent
+    pub fn reparent(&mut self, index: usize, parent: usize) -> Result<(), PrefabError> {
+        if index >= self.entidades.len() {
+            return Err(PrefabError::MissingEntity(index));
+        }
+        if parent >= self.entidades.len() {
+            return Err(PrefabError::MissingParent {
+                entity: index,
+                parent,
+            });
+        }
+        if index == 0 {
+            return Err(PrefabError::InvalidEntity {
+                entity: 0,
+                reason: "The root can't have a parent".to_string(),
+            });
+        }
+        // The new parent can't be a descendant of the entity
+        let mut atual = Some(parent);
+        for _ in 0..self.entidades.len() {
+            match atual {
+                Some(ancestral) if ancestral == index => return Err(PrefabError::Cycle(index)),
+                Some(ancestral) => atual = self.parent_of(ancestral),
+                None => break,
+            }
+        }
+        self.entidades[index].parent = Some(parent);
+        Ok(())
+    }
+
+    /// Remove the entity and its descendants, returned as a prefab with the entity as the root.
+    /// The indices of the entities after the removed ones change. The root can't be removed
+    pub fn remove_subtree(&mut self, index: usize) -> Option<Prefab<T, S>>
+    where
+        T: PrefabData,
+    {
+        if index == 0 || index >= self.entidades.len() {
+            return None;
+        }
+        // The index of the removed entities in the new prefab
+        let mut removidos = vec![None; self.entidades.len()];
+        for (novo, antigo) in self.depth_first(index).enumerate() {
+            removidos[antigo] = Some(novo);
+        }
+        // The index of the remaining entities
+        let mut restantes = vec![None; self.entidades.len()];
+        let mut total = 0;
+        for (antigo, removido) in removidos.iter().enumerate() {
+            if removido.is_none() {
+                restantes[antigo] = Some(total);
+                total += 1;
+            }
+        }
+
+        let mut subarvore: Vec<_> = removidos.iter().flatten().map(|_| None).collect();
+        for (antigo, mut entidade) in std::mem::take(&mut self.entidades).into_iter().enumerate() {
+            // The references follow the entity to its prefab
+            let indices = match removidos[antigo] {
+                Some(_) => &removidos,
+                None => &restantes,
+            };
+            if let Some(data) = entidade.data.as_mut() {
+                data.remap_refs(&|index| indices.get(index).copied().flatten());
+            }
+            if let Some(novo) = removidos[antigo] {
+                entidade.parent = match novo {
+                    0 => None,
+                    _ => entidade.parent.and_then(|p| removidos[p]),
+                };
+                subarvore[novo] = Some(entidade);
+            } else {
+                // Parents out of the prefab are kept as they are
+                entidade.parent = entidade
+                    .parent
+                    .map(|p| restantes.get(p).copied().flatten().unwrap_or(p));
+                self.entidades.push(entidade);
+            }
+        }
+        Some(Prefab {
+            entidades: subarvore.into_iter().flatten().collect(),
+            base: None,
+            source: None,
+            params: self.params.clone(),
+            substitute: self.substitute,
+            state: PhantomData,
+        })
+    }
+
+    /// Append all the entities of the other prefab, with its root as a child of the given entity.
+    /// Returns the new index of the other root
+    pub fn insert_subtree(
+        &mut self,
+        parent: usize,
+        other: Prefab<T, S>,
+    ) -> Result<usize, PrefabError>
+    where
+        T: PrefabData,
+    {
+        if parent >= self.entidades.len() {
+            return Err(PrefabError::MissingEntity(parent));
+        }
+        Ok(self.append(parent, other))
+    }
+
+    fn append<S2>(&mut self, parent: usize, other: Prefab<T, S2>) -> usize
+    where
+        T: PrefabData,
+    {
+        let offset = self.entidades.len();
+        let total = other.entidades.len();
+        let map = |index: usize| (index < total).then_some(index + offset);
+        for mut entidade in other.entidades {
+            entidade.parent = Some(entidade.parent.map_or(parent, |p| p + offset));
+            if let Some(data) = entidade.data.as_mut() {
+                data.remap_refs(&map);
+            }
+            self.entidades.push(entidade);
+        }
+        for (name, default) in other.params {
+            self.params.entry(name).or_insert(default);
+        }
+        self.substitute = self.substitute.or(other.substitute);
+        offset
+    }
+
+    /// Return the handles of all the assets loaded by [`Prefab::prepare_entities`]
+    pub fn sub_assets(&self) -> Vec<UntypedHandle>
+    where
+        T: PrefabData,
+    {
+        let mut handles = Vec::new();
+        for entidade in self.entidades.iter() {
+            if let Some(data) = entidade.get_data() {
+                data.collect_sub_assets(&mut handles);
+            }
+        }
+        handles
+    }
+
+    // The handles of the given entities, to report the ones that failed
+    fn sub_assets_of(&self, indices: &[usize]) -> Vec<UntypedHandle>
+    where
+        T: PrefabData,
+    {
+        let mut handles = Vec::new();
+        for index in indices {
+            if let Some(data) = self.entidades.get(*index).and_then(|e| e.get_data()) {
+                data.collect_sub_assets(&mut handles);
+            }
+        }
+        handles
+    }
+}
+impl<T> Prefab<T, Prepared> {
+    /// Spawn the prefab in a new root, the data of the spawned entities is taken from the prefab
+    pub fn spawn(
+        &mut self,
+        processed_children: &HashMap<usize, Vec<usize>>,
+        init: &usize,
+        world: &mut World,
+    ) -> Option<Entity>
+    where
+        T: PrefabData,
+    {
+        let root = world.spawn_empty().id();
+        let spawned = self.spawn_with_root(processed_children, init, world, root);
+        if spawned.is_err() {
+            despawn_with_children_recursive(world, root);
+        }
+        spawned.ok()
+    }
+
+    // Spawned with a single root, the other roots of a forest would be left out
+    fn single_root(&self) -> Result<(), PrefabError> {
+        match self.roots().nth(1) {
+            Some(extra) => Err(PrefabError::ExtraRoot(extra)),
+            None => Ok(()),
+        }
+    }
+
+    // The first entity will have your specified root
+    fn spawn_with_root(
+        &mut self,
+        processed_children: &HashMap<usize, Vec<usize>>,
+        init: &usize,
+        world: &mut World,
+        root: Entity,
+    ) -> Result<Entity, PrefabError>
+    where
+        T: PrefabData,
+    {
+        self.single_root()?;
+        self.spawn_with_roots(processed_children, &[(*init, root)], world)?;
+        Ok(root)
+    }
+
+    /// Spawn every root of the prefab as a new entity, see [`Prefab::roots`].
+    /// Returns the spawned roots, in the order of the prefab
+    pub fn spawn_forest(&mut self, world: &mut World) -> Option<Vec<Entity>>
+    where
+        T: PrefabData,
+    {
+        let roots: Vec<_> = self.roots().map(|_| world.spawn_empty().id()).collect();
+        if self.spawn_forest_in(world, &roots).is_err() {
+            for root in roots {
+                despawn_with_children_recursive(world, root);
+            }
+            return None;
+        }
+        Some(roots)
+    }
+
+    // The roots of the prefab are spawned in the given entities, in order
+    fn spawn_forest_in(&mut self, world: &mut World, roots: &[Entity]) -> Result<(), PrefabError>
+    where
+        T: PrefabData,
+    {
+        let roots: Vec<_> = self.roots().zip(roots.iter().copied()).collect();
+        self.spawn_with_roots(&self.all_parents_childs(), &roots, world)
+    }
+
+    fn spawn_with_roots(
+        &mut self,
+        processed_children: &HashMap<usize, Vec<usize>>,
+        roots: &[(usize, Entity)],
+        world: &mut World,
+    ) -> Result<(), PrefabError>
+    where
+        T: PrefabData,
+    {
+        let mut datas: Vec<_> = self
+            .entidades
+            .iter_mut()
+            .map(PrefabEntityBuilder::take_data)
+            .collect();
+        let spawned = self.spawn_instance(processed_children, roots, world, &mut |index| {
+            datas.get_mut(index).and_then(Option::take)
+        });
+        // The entities that weren't spawned keep their data
+        for (entidade, data) in self.entidades.iter_mut().zip(datas) {
+            if data.is_some() {
+                entidade.data = data;
+            }
+        }
+        spawned
+    }
+
+    /// Spawn a copy of the prefab, the data is cloned so the prefab can be spawned again.
+    /// Like [`Prefab::spawn`], the prefab must already be prepared
+    pub fn instantiate(&self, world: &mut World) -> Option<Entity>
+    where
+        T: PrefabData + Clone,
+    {
+        let root = world.spawn_empty().id();
+        let spawned = self.try_instantiate_in(&self.all_parents_childs(), world, root);
+        if spawned.is_err() {
+            despawn_with_children_recursive(world, root);
+        }
+        spawned.ok()
+    }
+
+    /// Spawn a copy of the prefab in the given root, that must exist
+    pub fn instantiate_in(
+        &self,
+        processed_children: &HashMap<usize, Vec<usize>>,
+        world: &mut World,
+        root: Entity,
+    ) -> Option<Entity>
+    where
+        T: PrefabData + Clone,
+    {
+        self.try_instantiate_in(processed_children, world, root)
+            .ok()
+    }
+
+    fn try_instantiate_in(
+        &self,
+        processed_children: &HashMap<usize, Vec<usize>>,
+        world: &mut World,
+        root: Entity,
+    ) -> Result<Entity, PrefabError>
+    where
+        T: PrefabData + Clone,
+    {
+        self.single_root()?;
+        self.spawn_instance(processed_children, &[(0, root)], world, &mut |index| {
+            self.entidades.get(index)?.get_data().cloned()
+        })?;
+        Ok(root)
+    }
+
+    // Every root shares the same instance
+    fn spawn_instance(
+        &self,
+        processed_children: &HashMap<usize, Vec<usize>>,
+        roots: &[(usize, Entity)],
+        world: &mut World,
+        data: &mut dyn FnMut(usize) -> Option<T>,
+    ) -> Result<(), PrefabError>
+    where
+        T: PrefabData,
+    {
+        let Some((_, first)) = roots.first() else {
+            return Err(PrefabError::MissingEntity(0));
+        };
+        if let Some((_, root)) = roots.iter().find(|(_, r)| world.get_entity(*r).is_none()) {
+            return Err(PrefabError::MissingRoot(*root));
+        }
+        // Every root is checked before spawning, so a failure doesn't leave half a forest
+        if let Some((init, _)) = roots
+            .iter()
+            .find(|(init, _)| !processed_children.contains_key(init))
+        {
+            return Err(PrefabError::MissingEntity(*init));
+        }
+        let mut instance = PrefabInstance::new(*first, self.source.clone());
+        instance.roots = roots.iter().map(|(_, root)| *root).collect();
+        // The first entity with each name, like `Prefab::index_by_name`
+        for (index, entidade) in self.entidades.iter().enumerate().rev() {
+            if let Some(name) = entidade.get_name() {
+                instance.names.insert(name.to_string(), index);
+            }
+        }
+        // The data is only taken once every root is spawned, so nothing is lost on a failure,
+        // and inserted once every entity exists, so the references can be resolved
+        let mut pendentes = Vec::new();
+        let mut take = |index, entity| pendentes.push((index, entity));
+        for (init, root) in roots {
+            if !self.spawn_tree(
+                processed_children,
+                *init,
+                world,
+                *root,
+                &mut instance,
+                &mut take,
+            ) {
+                return Err(PrefabError::MissingEntity(*init));
+            }
+        }
+        let datas: Vec<_> = pendentes
+            .into_iter()
+            .filter_map(|(index, entity)| Some((entity, data(index)?)))
+            .collect();
+        for (entity, mut data) in datas {
+            data.resolve_refs(&instance);
+            data.insert_into_entity(&mut world.entity_mut(entity));
+        }
+        for (_, root) in roots {
+            world.entity_mut(*root).insert(instance.clone());
+        }
+        Ok(())
+    }
+
+    // Spawn the entity of the index in the given entity, that must exist, then its children.
+    // The data is given to `take`, to be inserted once the whole tree is spawned
+    fn spawn_tree(
+        &self,
+        processed_children: &HashMap<usize, Vec<usize>>,
+        index: usize,
+        world: &mut World,
+        entity: Entity,
+        instance: &mut PrefabInstance,
+        take: &mut dyn FnMut(usize, Entity),
+    ) -> bool
+    where
+        T: PrefabData,
+    {
+        let Some(children) = processed_children.get(&index) else {
+            // Probably an error with wrong adding the children.
+            warn!(target: "prefab", "[warning] Entity not found in the prefab with index {}", index);
+            return false;
+        };
+        if instance.entity(index).is_some() {
+            warn!(target: "prefab", "[warning] Entity {} is its own ancestor", index);
+            return false;
+        }
+
+        take(index, entity);
+        let mut entidade_mundo = world.entity_mut(entity);
+        if let Some(name) = self.entidades.get(index).and_then(|e| e.get_name()) {
+            entidade_mundo.insert(Name::new(name.to_string()));
+        }
+        entidade_mundo.insert(PrefabEntity::<T>::new(index, instance.root()));
+        instance.insert(index, entity);
+
+        for child in children {
+            let child_entity = world.spawn_empty().id();
+            if self.spawn_tree(
+                processed_children,
+                *child,
+                world,
+                child_entity,
+                instance,
+                take,
+            ) {
+                world.entity_mut(entity).add_child(child_entity);
+            } else {
+                world.despawn(child_entity);
+            }
+        }
+        true
+    }
+}
+impl<T> Default for Prefab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T, S> Clone for Prefab<T, S>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            entidades: self.entidades.clone(),
+            base: self.base.clone(),
+            source: self.source.clone(),
+            params: self.params.clone(),
+            substitute: self.substitute,
+            state: PhantomData,
+        }
+    }
+}
+
+pub struct PrefabEntityBuilder<T> {
+    parent: Option<usize>,
+    data: Option<T>,
+    reference: Option<String>,
+    target: Option<String>,
+    name: Option<String>,
+}
+impl<T> PrefabEntityBuilder<T> {
+    pub fn new(parent: Option<usize>, data: Option<T>) -> Self {
+        Self {
+            parent,
+            data,
+            reference: None,
+            target: None,
+            name: None,
+        }
+    }
+
+    pub fn get_data_or_insert_with(&mut self, f: impl FnOnce() -> T) -> &mut T {
+        self.data.get_or_insert_with(f)
+    }
+
+    pub fn get_data_or_default(&mut self) -> &mut T
+    where
+        T: Default,
+    {
+        self.data.get_or_insert_with(T::default)
+    }
+
+    pub fn get_data(&self) -> Option<&T> {
+        self.data.as_ref()
+    }
+
+    pub fn get_data_mut(&mut self) -> Option<&mut T> {
+        self.data.as_mut()
+    }
+
+    pub fn set_parent(&mut self, parent: usize) {
+        self.parent = Some(parent);
+    }
+
+    pub fn set_data(&mut self, data: T) {
+        self.data = Some(data);
+    }
+
+    pub fn take_data(&mut self) -> Option<T> {
+        self.data.take()
+    }
+
+    /// The path of the prefab that will be spawned under this entity
+    pub fn get_reference(&self) -> Option<&str> {
+        self.reference.as_deref()
+    }
+
+    pub fn set_reference(&mut self, path: impl Into<String>) {
+        self.reference = Some(path.into());
+    }
+
+    pub fn take_reference(&mut self) -> Option<String> {
+        self.reference.take()
+    }
+
+    /// The name of the entity, spawned as a [`Name`] component
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = Some(name.into());
+    }
+
+    /// The path of the entity of the base prefab changed by this one, see [`Prefab::add_override`]
+    pub fn get_target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    pub fn prepare_data(&mut self, world: &mut World) -> bool
+    where
+        T: PrefabData,
+    {
+        if let Some(data) = self.get_data_mut() {
+            data.load_sub_assets(world)
+        } else {
+            false
+        }
+    }
+}
+impl<T> Default for PrefabEntityBuilder<T> {
+    fn default() -> Self {
+        Self {
+            parent: None,
+            data: None,
+            reference: None,
+            target: None,
+            name: None,
+        }
+    }
+}
+impl<T> Clone for PrefabEntityBuilder<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            parent: self.parent.clone(),
+            data: self.data.clone(),
+            reference: self.reference.clone(),
+            target: self.target.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
+pub trait PrefabData: Send + Sync + 'static {
+    /// Transform self into a component and insert into an entity
+    fn insert_into_entity(self, entidade: &mut EntityWorldMut);
+    fn load_sub_assets(&mut self, _world: &mut World) -> bool {
+        false
+    }
+    /// Push the handles loaded in `load_sub_assets`, so the spawn can wait for them
+    fn collect_sub_assets(&self, _handles: &mut Vec<UntypedHandle>) {}
+    /// Resolve the [`PrefabRef`]s of the data, called before `insert_into_entity`,
+    /// once every entity of the prefab is spawned
+    fn resolve_refs(&mut self, _instance: &PrefabInstance) {}
+    /// Give the new index to the [`PrefabRef::Index`]s of the data, called when the entities
+    /// of the prefab move (nested prefabs, variants, [`Prefab::remove_subtree`]...).
+    /// The map gives None for the entities that left the prefab
+    fn remap_refs(&mut self, _map: &dyn Fn(usize) -> Option<usize>) {}
+    /// Apply the data of a prefab variant over this one, replacing it by default
+    fn merge(&mut self, other: Self)
+    where
+        Self: Sized,
+    {
+        *self = other;
+    }
+}
+
+/// Suport trait for simple data that can be easily converted to a Component
+/// PrefabData is implemented for those who implement this trait + Clone
+/// Mustn't implement this trait the Components based on assets loading.
+pub trait IntoComponent {
+    type Component: Component;
+    fn into_component(self) -> Self::Component;
+    /// See [`PrefabData::resolve_refs`]
+    fn resolve_refs(&mut self, _instance: &PrefabInstance) {}
+    /// See [`PrefabData::remap_refs`]
+    fn remap_refs(&mut self, _map: &dyn Fn(usize) -> Option<usize>) {}
+}
+
+/// The counterpart of [`IntoComponent`], turn the component back into the data
+pub trait FromComponent: IntoComponent {
+    fn from_component(component: &Self::Component) -> Self;
+}
+
+/// The counterpart of [`PrefabData`], read the data back from the components of an entity.
+/// Implemented for those who implement [`FromComponent`]
+pub trait ExtractPrefabData: Sized {
+    /// Returns None if the entity doesn't have the needed components
+    fn extract_from_entity(entidade: &EntityRef) -> Option<Self>;
+}
+
+/// The formatter used for deserialize purposes
+pub trait Format<PD>
+where
+    Self: Send + Sync,
+{
+    fn load_from_bytes(bytes: Vec<u8>) -> Result<Prefab<PD>, PrefabError>;
+}
+
+/// The formatter used for serialize purposes, the counterpart of [`Format`]
+pub trait FormatWriter<PD>
+where
+    Self: Send + Sync,
+{
+    fn save_to_bytes(prefab: &Prefab<PD>) -> Result<Vec<u8>, PrefabError>;
+}
+
+#[derive(Resource, Default)]
+/// Helper to some prefab functions
+pub struct PrefabLoader;
+
+impl PrefabLoader {
+    /// Load the prefab, its base and every prefab referenced by its entities
+    pub fn create_prefab<T: PrefabData, F: Format<T>, S: AsRef<Path>>(
+        nome: S,
+    ) -> Result<Prefab<T>, PrefabError> {
+        let path = nome.as_ref();
+        let mut prefab = Self::read_prefab::<T, F>(path)?;
+        Self::resolve_references::<T, F>(&mut prefab, path)?;
+        prefab.debug_validate_forest()?;
+        prefab.set_source(path.display().to_string());
+        Ok(prefab)
+    }
+
+    /// Load the base of the prefab and the prefabs referenced by the entities (and their own ones)
+    /// with the given formatter, then merge them into the prefab.
+    /// - path: The file of the prefab, the references are relative to it
+    pub fn resolve_references<T: PrefabData, F: Format<T>>(
+        prefab: &mut Prefab<T>,
+        path: &Path,
+    ) -> Result<(), PrefabError> {
+        Self::resolve_nested::<T, F>(prefab, path, &mut vec![path.to_path_buf()])
+    }
+
+    fn resolve_nested<T: PrefabData, F: Format<T>>(
+        prefab: &mut Prefab<T>,
+        path: &Path,
+        ancestors: &mut Vec<PathBuf>,
+    ) -> Result<(), PrefabError> {
+        if let Some(base) = prefab.take_base() {
+            let base = Self::load_nested::<T, F>(&resolve_reference(path, &base), ancestors)?;
+            *prefab = base.apply_variant(std::mem::take(prefab))?;
+        }
+        for (index, reference) in prefab.take_references() {
+            let nested =
+                Self::load_nested::<T, F>(&resolve_reference(path, &reference), ancestors)?;
+            prefab.graft(index, nested);
+        }
+        Ok(())
+    }
+
+    fn load_nested<T: PrefabData, F: Format<T>>(
+        path: &Path,
+        ancestors: &mut Vec<PathBuf>,
+    ) -> Result<Prefab<T>, PrefabError> {
+        if ancestors.iter().any(|a| a == path) {
+            return Err(PrefabError::ReferenceCycle(path.to_path_buf()));
+        }
+        ancestors.push(path.to_path_buf());
+        let mut prefab = Self::read_prefab::<T, F>(path)?;
+        Self::resolve_nested::<T, F>(&mut prefab, path, ancestors)?;
+        ancestors.pop();
+        Ok(prefab)
+    }
+
+    fn read_prefab<T: PrefabData, F: Format<T>>(path: &Path) -> Result<Prefab<T>, PrefabError> {
+        let bytes =
+            Self::load_prefab(path).map_err(|e| PrefabError::from(e).with_source(path, &[]))?;
+        // The bytes are kept to show where the error is
+        F::load_from_bytes(bytes.clone()).map_err(|e| e.with_source(path, &bytes))
+    }
+
+    /// Write the prefab in the given path, with the given formatter
+    /// - T: The prefab data that all the entities are bound for.
+    /// - F: The formatter for writing the prefab.
+    /// - S: The relative path of the prefab
+    pub fn save_prefab<T, F: FormatWriter<T>, S: AsRef<Path>>(
+        prefab: &Prefab<T>,
+        nome: S,
+    ) -> Result<(), PrefabError> {
+        let path = nome.as_ref();
+        let bytes = F::save_to_bytes(prefab)?;
+        fs::write(path, bytes).map_err(|e| PrefabError::from(e).with_source(path, &[]))?;
+        Ok(())
+    }
+
+    /// Instead of spawning the prefab, just create the prefab
+    /// - T: The prefab data that all the entities are bound for.
+    /// - F: The formatter for loading the prefab.
+    /// - S: The relative path of the prefab
+    // pub fn lazy_create<T: PrefabData, F: Format<T>, S: AsRef<Path>>(&self, world: &mut World, nome: S) -> Result<Prefab<T>, Box<dyn Error>>{
+    //     let mut prefab = F::load_from_bytes(Self::load_prefab(nome)?)?;
+
+    //     prefab.prepare_entities(world);
+    //     Ok(prefab)
+    // }
+
+    fn load_prefab<S: AsRef<Path>>(path: S) -> Result<Vec<u8>, io::Error> {
+        let path = path.as_ref();
         let content: Vec<u8> = {
             let mut file = fs::File::open(path)?;
             let mut buffer = Vec::new();
             file.read_to_end(&mut buffer)?;
 
-            buffer
+            buffer
+        };
+        Ok(content)
+    }
+}
+/// The path of the reference, relative to the directory of the file
+pub(crate) fn resolve_reference(file: &Path, reference: &str) -> PathBuf {
+    let reference = Path::new(reference);
+    // Absolute references don't depend on the file
+    let base = match reference.has_root() {
+        true => Path::new(""),
+        false => file.parent().unwrap_or(Path::new("")),
+    };
+    let mut resultado = PathBuf::new();
+    for componente in base.components().chain(reference.components()) {
+        match componente {
+            PathComponent::CurDir => {}
+            PathComponent::ParentDir => match resultado.components().next_back() {
+                Some(PathComponent::Normal(_)) => {
+                    resultado.pop();
+                }
+                // Nothing left to go back, keep the `..`
+                _ => resultado.push(".."),
+            },
+            _ => resultado.push(componente),
+        }
+    }
+    resultado
+}
+
+pub struct PrefabCommands<'c, 'w, 's, PD: PrefabData, S = Unprepared> {
+    prefab: Prefab<PD, S>,
+    commands: &'c mut Commands<'w, 's>,
+    /// The file of the prefab, given to the spawned root for the hot reload
+    file: Option<PrefabFile<PD>>,
+}
+impl<'c, 'w, 's, PD: PrefabData, S> PrefabCommands<'c, 'w, 's, PD, S> {
+    pub fn get_entity(&self, index: usize) -> Option<&PrefabEntityBuilder<PD>> {
+        self.prefab.get_entity(index)
+    }
+
+    pub fn commands(&mut self) -> &mut Commands<'w, 's> {
+        self.commands
+    }
+
+    pub fn prefab(self) -> Prefab<PD, S> {
+        self.prefab
+    }
+}
+impl<'c, 'w, 's, PD: PrefabData> PrefabCommands<'c, 'w, 's, PD> {
+    pub fn add(&mut self, parent: Option<usize>, data: Option<PD>) -> usize {
+        self.prefab.add(parent, data)
+    }
+
+    /// See [`Prefab::get_entity_mut`]
+    pub fn get_entity_mut(&mut self, index: usize) -> Option<&mut PrefabEntityBuilder<PD>> {
+        self.prefab.get_entity_mut(index)
+    }
+
+    pub fn get_entity_by_name_mut(&mut self, name: &str) -> Option<&mut PrefabEntityBuilder<PD>> {
+        self.prefab.get_entity_by_name_mut(name)
+    }
+
+    /// See [`Prefab::index_by_path`]
+    pub fn get_entity_by_path_mut(&mut self, path: &str) -> Option<&mut PrefabEntityBuilder<PD>> {
+        self.prefab.get_entity_by_path_mut(path)
+    }
+
+    /// Give the arguments to the parameters of the prefab, see [`Prefab::apply_args`].
+    /// If they can't be applied, the error is logged and None is returned, so nothing is spawned
+    pub fn with_args(self, args: PrefabArgs) -> Option<Self>
+    where
+        PD: Serialize + DeserializeOwned,
+    {
+        self.try_with_args(args)
+            .map_err(|e| error!(target: "prefab", "[error] Prefab Error: {e}"))
+            .ok()
+    }
+
+    /// Give the arguments to the parameters of the prefab, or return why they can't be applied
+    pub fn try_with_args(mut self, args: PrefabArgs) -> Result<Self, PrefabError>
+    where
+        PD: Serialize + DeserializeOwned,
+    {
+        self.prefab.apply_args(&args)?;
+        if let Some(file) = self.file.as_mut() {
+            file.with_args(args);
+        }
+        Ok(self)
+    }
+
+    // The root is reloaded when the file changes, if the prefab came from a file
+    // The file is kept in the first root, with the other roots of a forest
+    fn watch(
+        commands: &mut Commands,
+        roots: &[Entity],
+        forest: bool,
+        file: Option<PrefabFile<PD>>,
+    ) {
+        let (Some(mut file), Some((root, others))) = (file, roots.split_first()) else {
+            return;
+        };
+        let root = *root;
+        file.forest = forest.then(|| others.to_vec());
+        commands.add(move |world: &mut World| {
+            if let Some(mut entidade) = world.get_entity_mut(root) {
+                entidade.insert(file);
+            }
+        });
+    }
+
+    pub fn prepare_spawn_empty(self) -> Entity {
+        let root = self.commands.prepare_and_spawn_prefab(self.prefab);
+        Self::watch(self.commands, &[root], false, self.file);
+        root
+    }
+
+    pub fn prepare_spawn_as_child_of(self, parent: Entity) -> Entity {
+        let root = self
+            .commands
+            .prepare_and_spawn_prefab_as_child_of(parent, self.prefab);
+        Self::watch(self.commands, &[root], false, self.file);
+        root
+    }
+
+    pub fn prepare_spawn_forest(self) -> Vec<Entity> {
+        let roots = self.commands.prepare_and_spawn_prefab_forest(self.prefab);
+        Self::watch(self.commands, &roots, true, self.file);
+        roots
+    }
+
+    pub fn prepare_spawn_forest_as_children_of(self, parent: Entity) -> Vec<Entity> {
+        let roots = self
+            .commands
+            .prepare_and_spawn_prefab_forest_as_children_of(parent, self.prefab);
+        Self::watch(self.commands, &roots, true, self.file);
+        roots
+    }
+
+    pub fn prepare_spawn<B: Bundle>(self, bundle: B) -> Entity {
+        let root = self
+            .commands
+            .prepare_and_spawn_prefab_with(self.prefab, bundle);
+        Self::watch(self.commands, &[root], false, self.file);
+        root
+    }
+
+    pub fn prepare_spawn_empty_when_loaded(self) -> Entity {
+        let root = self
+            .commands
+            .prepare_and_spawn_prefab_when_loaded(self.prefab);
+        Self::watch(self.commands, &[root], false, self.file);
+        root
+    }
+
+    pub fn prepare_spawn_when_loaded<B: Bundle>(self, bundle: B) -> Entity {
+        let root = self
+            .commands
+            .prepare_and_spawn_prefab_when_loaded_with(self.prefab, bundle);
+        Self::watch(self.commands, &[root], false, self.file);
+        root
+    }
+
+    pub fn prepare(self) -> PrefabRequestId {
+        self.commands.prepare_prefab(self.prefab)
+    }
+
+    pub fn prepare_stored(self) -> PrefabRequestId {
+        self.commands.prepare_prefab_stored(self.prefab)
+    }
+}
+impl<'c, 'w, 's, PD: PrefabData> PrefabCommands<'c, 'w, 's, PD, Prepared> {
+    pub fn spawn<B: Bundle>(self, bundle: B) -> Entity {
+        self.commands.spawn_prefab_with(self.prefab, bundle)
+    }
+
+    pub fn spawn_empty(self) -> Entity {
+        self.commands.spawn_prefab(self.prefab)
+    }
+
+    pub fn spawn_as_child_of(self, parent: Entity) -> Entity {
+        self.commands.spawn_prefab_as_child_of(parent, self.prefab)
+    }
+
+    pub fn spawn_forest(self) -> Vec<Entity> {
+        self.commands.spawn_prefab_forest(self.prefab)
+    }
+
+    pub fn spawn_forest_as_children_of(self, parent: Entity) -> Vec<Entity> {
+        self.commands
+            .spawn_prefab_forest_as_children_of(parent, self.prefab)
+    }
+}
+
+pub trait PrefabCommandsExt<'c, 'w, 's> {
+    /// Load the prefab and create the commands.
+    /// If the prefab can't be loaded, the error is logged and an empty prefab is used.
+    ///
+    /// The roots spawned by the commands are spawned again when the file changes,
+    /// with the hot reload of the [`PrefabPlugin`]
+    fn load_prefab<PD: PrefabData, F: Format<PD>, S: AsRef<Path>>(
+        &'c mut self,
+        name: S,
+    ) -> PrefabCommands<'c, 'w, 's, PD>;
+
+    /// Load the prefab and create the commands, or return why it can't be loaded
+    fn try_load_prefab<PD: PrefabData, F: Format<PD>, S: AsRef<Path>>(
+        &'c mut self,
+        name: S,
+    ) -> Result<PrefabCommands<'c, 'w, 's, PD>, PrefabError>;
+
+    /// Spawn an external prefab, already prepared
+    fn spawn_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD, Prepared>) -> Entity;
+
+    /// Spawn an external prefab, already prepared, with a bundle in the parent
+    fn spawn_prefab_with<PD: PrefabData, B: Bundle>(
+        &mut self,
+        prefab: Prefab<PD, Prepared>,
+        bundle: B,
+    ) -> Entity;
+
+    /// Spawn an external prefab, already prepared, as a child of the given entity
+    fn spawn_prefab_as_child_of<PD: PrefabData>(
+        &mut self,
+        parent: Entity,
+        prefab: Prefab<PD, Prepared>,
+    ) -> Entity;
+
+    /// Spawn every root of an external prefab, already prepared, as a new entity.
+    /// Returns the roots, in the order of [`Prefab::roots`]
+    fn spawn_prefab_forest<PD: PrefabData>(&mut self, prefab: Prefab<PD, Prepared>) -> Vec<Entity>;
+
+    /// Spawn every root of an external prefab, already prepared, as children of the given entity
+    fn spawn_prefab_forest_as_children_of<PD: PrefabData>(
+        &mut self,
+        parent: Entity,
+        prefab: Prefab<PD, Prepared>,
+    ) -> Vec<Entity>;
+
+    /// Prepare the assets and then Spawn every root of the prefab
+    fn prepare_and_spawn_prefab_forest<PD: PrefabData>(
+        &mut self,
+        prefab: Prefab<PD>,
+    ) -> Vec<Entity>;
+
+    /// Prepare the assets and then Spawn every root of the prefab as children of the given entity
+    fn prepare_and_spawn_prefab_forest_as_children_of<PD: PrefabData>(
+        &mut self,
+        parent: Entity,
+        prefab: Prefab<PD>,
+    ) -> Vec<Entity>;
+
+    /// Prepare the assets and then Spawn
+    fn prepare_and_spawn_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> Entity;
+
+    /// Prepare the assets and then Spawn as a child of the given entity
+    fn prepare_and_spawn_prefab_as_child_of<PD: PrefabData>(
+        &mut self,
+        parent: Entity,
+        prefab: Prefab<PD>,
+    ) -> Entity;
+
+    /// Prepare the assets and then Spawn with the given bundle
+    fn prepare_and_spawn_prefab_with<PD: PrefabData, B: Bundle>(
+        &mut self,
+        prefab: Prefab<PD>,
+        bundle: B,
+    ) -> Entity;
+
+    /// Prepare the assets and then Spawn only when all of them are loaded.
+    /// If any asset fail to load, a [`FailedPrefab`] event is sent instead
+    fn prepare_and_spawn_prefab_when_loaded<PD: PrefabData>(
+        &mut self,
+        prefab: Prefab<PD>,
+    ) -> Entity;
+
+    /// Prepare the assets and then Spawn with the given bundle only when all of them are loaded.
+    /// If any asset fail to load, a [`FailedPrefab`] event is sent instead
+    fn prepare_and_spawn_prefab_when_loaded_with<PD: PrefabData, B: Bundle>(
+        &mut self,
+        prefab: Prefab<PD>,
+        bundle: B,
+    ) -> Entity;
+
+    /// Take the prefab and will prepare, when loaded, the prefab will be returned as a [`LoadedPrefab`] event
+    /// with the returned id
+    fn prepare_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> PrefabRequestId;
+
+    /// Take the prefab and will prepare, when loaded, the prefab can be taken from the
+    /// [`PreparedPrefabs`] resource with the returned id
+    fn prepare_prefab_stored<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> PrefabRequestId;
+
+    /// Spawn a copy of a shared prefab, that must already be prepared.
+    /// Only the data of the entities is cloned, see [`Prefab::instantiate`]
+    fn spawn_prefab_instance<PD: PrefabData + Clone>(
+        &mut self,
+        prefab: &Arc<Prefab<PD, Prepared>>,
+    ) -> Entity;
+
+    /// Spawn a copy of a shared prefab with a bundle in the parent
+    fn spawn_prefab_instance_with<PD: PrefabData + Clone, B: Bundle>(
+        &mut self,
+        prefab: &Arc<Prefab<PD, Prepared>>,
+        bundle: B,
+    ) -> Entity;
+
+    /// Spawn a copy of a shared prefab for each transform, given to the root of the copy
+    fn spawn_prefab_instances<PD: PrefabData + Clone>(
+        &mut self,
+        prefab: &Arc<Prefab<PD, Prepared>>,
+        transforms: impl IntoIterator<Item = Transform>,
+    ) -> Vec<Entity>;
+
+    /// Create prefab commands from a external prefab
+    fn prefab_into_commands<PD: PrefabData, S>(
+        &'c mut self,
+        prefab: Prefab<PD, S>,
+    ) -> PrefabCommands<'c, 'w, 's, PD, S>;
+}
+
+impl<'c, 'w, 's> PrefabCommandsExt<'c, 'w, 's> for Commands<'w, 's> {
+    fn load_prefab<PD: PrefabData, F: Format<PD>, S: AsRef<Path>>(
+        &'c mut self,
+        name: S,
+    ) -> PrefabCommands<'c, 'w, 's, PD> {
+        let file = PrefabFile::new::<F>(name.as_ref());
+        let prefab = PrefabLoader::create_prefab::<PD, F, S>(name);
+        let prefab = if let Err(e) = prefab {
+            error!(target: "prefab", "[error] Prefab Error: {e}");
+            Default::default()
+        } else {
+            prefab.unwrap()
+        };
+        // Even an empty prefab is reloaded, so the file can be fixed
+        PrefabCommands {
+            prefab,
+            commands: self,
+            file: Some(file),
+        }
+    }
+
+    fn try_load_prefab<PD: PrefabData, F: Format<PD>, S: AsRef<Path>>(
+        &'c mut self,
+        name: S,
+    ) -> Result<PrefabCommands<'c, 'w, 's, PD>, PrefabError> {
+        let file = PrefabFile::new::<F>(name.as_ref());
+        let prefab = PrefabLoader::create_prefab::<PD, F, S>(name)?;
+        Ok(PrefabCommands {
+            prefab,
+            commands: self,
+            file: Some(file),
+        })
+    }
+
+    fn spawn_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD, Prepared>) -> Entity {
+        let entity = self.spawn_empty().id();
+
+        self.add(SpawnPrefab(prefab, entity));
+        entity
+    }
+
+    fn spawn_prefab_with<PD: PrefabData, B: Bundle>(
+        &mut self,
+        prefab: Prefab<PD, Prepared>,
+        bundle: B,
+    ) -> Entity {
+        let entity = self.spawn(bundle).id();
+
+        self.add(SpawnPrefab(prefab, entity.clone()));
+        entity
+    }
+
+    fn spawn_prefab_as_child_of<PD: PrefabData>(
+        &mut self,
+        parent: Entity,
+        prefab: Prefab<PD, Prepared>,
+    ) -> Entity {
+        let entity = self.spawn_empty().id();
+
+        self.add(SpawnPrefabAsChild {
+            prefab: PrefabToSpawn::Prepared(prefab),
+            root: entity,
+            parent,
+        });
+        entity
+    }
+
+    fn prepare_and_spawn_prefab_as_child_of<PD: PrefabData>(
+        &mut self,
+        parent: Entity,
+        prefab: Prefab<PD>,
+    ) -> Entity {
+        let entity = self.spawn_empty().id();
+
+        self.add(SpawnPrefabAsChild {
+            prefab: PrefabToSpawn::Unprepared(prefab),
+            root: entity,
+            parent,
+        });
+        entity
+    }
+
+    fn spawn_prefab_forest<PD: PrefabData>(&mut self, prefab: Prefab<PD, Prepared>) -> Vec<Entity> {
+        spawn_forest_command(self, PrefabToSpawn::Prepared(prefab), None)
+    }
+
+    fn spawn_prefab_forest_as_children_of<PD: PrefabData>(
+        &mut self,
+        parent: Entity,
+        prefab: Prefab<PD, Prepared>,
+    ) -> Vec<Entity> {
+        spawn_forest_command(self, PrefabToSpawn::Prepared(prefab), Some(parent))
+    }
+
+    fn prepare_and_spawn_prefab_forest<PD: PrefabData>(
+        &mut self,
+        prefab: Prefab<PD>,
+    ) -> Vec<Entity> {
+        spawn_forest_command(self, PrefabToSpawn::Unprepared(prefab), None)
+    }
+
+    fn prepare_and_spawn_prefab_forest_as_children_of<PD: PrefabData>(
+        &mut self,
+        parent: Entity,
+        prefab: Prefab<PD>,
+    ) -> Vec<Entity> {
+        spawn_forest_command(self, PrefabToSpawn::Unprepared(prefab), Some(parent))
+    }
+
+    fn prefab_into_commands<PD: PrefabData, S>(
+        &'c mut self,
+        prefab: Prefab<PD, S>,
+    ) -> PrefabCommands<'c, 'w, 's, PD, S> {
+        PrefabCommands {
+            prefab,
+            commands: self,
+            file: None,
+        }
+    }
+
+    fn prepare_and_spawn_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> Entity {
+        let entity = self.spawn_empty().id();
+
+        self.add(PrepareAndSpawnPrefab(prefab, entity.clone()));
+        entity
+    }
+
+    fn prepare_and_spawn_prefab_with<PD: PrefabData, B: Bundle>(
+        &mut self,
+        prefab: Prefab<PD>,
+        bundle: B,
+    ) -> Entity {
+        let entity = self.spawn(bundle).id();
+
+        self.add(PrepareAndSpawnPrefab(prefab, entity.clone()));
+        entity
+    }
+
+    fn prepare_and_spawn_prefab_when_loaded<PD: PrefabData>(
+        &mut self,
+        prefab: Prefab<PD>,
+    ) -> Entity {
+        let entity = self.spawn_empty().id();
+
+        self.add(PrepareAndSpawnPrefabWhenLoaded(prefab, entity));
+        entity
+    }
+
+    fn prepare_and_spawn_prefab_when_loaded_with<PD: PrefabData, B: Bundle>(
+        &mut self,
+        prefab: Prefab<PD>,
+        bundle: B,
+    ) -> Entity {
+        let entity = self.spawn(bundle).id();
+
+        self.add(PrepareAndSpawnPrefabWhenLoaded(prefab, entity));
+        entity
+    }
+
+    fn prepare_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> PrefabRequestId {
+        let command = PreparePrefab::new(prefab);
+        let id = command.id();
+        self.add(command);
+        id
+    }
+
+    fn prepare_prefab_stored<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> PrefabRequestId {
+        let command = PreparePrefab::new(prefab).stored();
+        let id = command.id();
+        self.add(command);
+        id
+    }
+
+    fn spawn_prefab_instance<PD: PrefabData + Clone>(
+        &mut self,
+        prefab: &Arc<Prefab<PD, Prepared>>,
+    ) -> Entity {
+        let entity = self.spawn_empty().id();
+
+        self.add(SpawnPrefabInstance(prefab.clone(), entity));
+        entity
+    }
+
+    fn spawn_prefab_instance_with<PD: PrefabData + Clone, B: Bundle>(
+        &mut self,
+        prefab: &Arc<Prefab<PD, Prepared>>,
+        bundle: B,
+    ) -> Entity {
+        let entity = self.spawn(bundle).id();
+
+        self.add(SpawnPrefabInstance(prefab.clone(), entity));
+        entity
+    }
+
+    fn spawn_prefab_instances<PD: PrefabData + Clone>(
+        &mut self,
+        prefab: &Arc<Prefab<PD, Prepared>>,
+        transforms: impl IntoIterator<Item = Transform>,
+    ) -> Vec<Entity> {
+        let roots: Vec<_> = transforms
+            .into_iter()
+            .map(|transform| (self.spawn_empty().id(), transform))
+            .collect();
+        let entities = roots.iter().map(|(entity, _)| *entity).collect();
+
+        self.add(SpawnPrefabInstances::new(prefab.clone(), roots));
+        entities
+    }
+}
+
+// An empty entity is spawned for each root of the prefab
+fn spawn_forest_command<PD: PrefabData>(
+    commands: &mut Commands,
+    prefab: PrefabToSpawn<PD>,
+    parent: Option<Entity>,
+) -> Vec<Entity> {
+    let total = match &prefab {
+        PrefabToSpawn::Prepared(prefab) => prefab.roots().count(),
+        PrefabToSpawn::Unprepared(prefab) => prefab.roots().count(),
+    };
+    let roots: Vec<_> = (0..total).map(|_| commands.spawn_empty().id()).collect();
+    commands.add(SpawnPrefabForest {
+        prefab,
+        roots: roots.clone(),
+        parent,
+    });
+    roots
+}
+
+pub struct SpawnPrefab<PD: PrefabData>(Prefab<PD, Prepared>, Entity);
+impl<PD: PrefabData> Command for SpawnPrefab<PD> {
+    fn apply(mut self, world: &mut World) {
+        if let Err(error) = self.0.debug_validate() {
+            fail_spawn(world, self.1, error);
+            return;
+        }
+        let processed_children = self.0.all_parents_childs();
+
+        let resultado = self
+            .0
+            .spawn_with_root(&processed_children, &0, world, self.1);
+        send_spawn_result(world, self.1, resultado);
+    }
+}
+
+/// Spawn the prefab in the root and add it to the children of the parent
+pub struct SpawnPrefabAsChild<PD: PrefabData> {
+    prefab: PrefabToSpawn<PD>,
+    root: Entity,
+    parent: Entity,
+}
+
+// The unprepared prefabs are prepared before spawning
+enum PrefabToSpawn<PD> {
+    Prepared(Prefab<PD, Prepared>),
+    Unprepared(Prefab<PD>),
+}
+impl<PD: PrefabData> Command for SpawnPrefabAsChild<PD> {
+    fn apply(self, world: &mut World) {
+        // Adding a missing child would panic
+        if world.get_entity(self.root).is_none() {
+            fail_spawn(world, self.root, PrefabError::MissingRoot(self.root));
+            return;
+        }
+        let Some(mut parent) = world.get_entity_mut(self.parent) else {
+            fail_spawn(
+                world,
+                self.root,
+                PrefabError::MissingParentEntity(self.parent),
+            );
+            // Without the parent, the root would be left alone
+            if let Some(root) = world.get_entity_mut(self.root) {
+                root.despawn_recursive();
+            }
+            return;
+        };
+        parent.add_child(self.root);
+
+        match self.prefab {
+            PrefabToSpawn::Prepared(prefab) => SpawnPrefab(prefab, self.root).apply(world),
+            PrefabToSpawn::Unprepared(prefab) => {
+                PrepareAndSpawnPrefab(prefab, self.root).apply(world)
+            }
+        }
+    }
+}
+
+/// Spawn every root of the prefab in the given entities, optionally as children of the parent.
+/// See [`PrefabCommandsExt::spawn_prefab_forest`]
+pub struct SpawnPrefabForest<PD: PrefabData> {
+    prefab: PrefabToSpawn<PD>,
+    roots: Vec<Entity>,
+    parent: Option<Entity>,
+}
+impl<PD: PrefabData> Command for SpawnPrefabForest<PD> {
+    fn apply(self, world: &mut World) {
+        // Adding a missing child would panic
+        if let Some(root) = self.roots.iter().find(|r| world.get_entity(**r).is_none()) {
+            fail_spawn_roots(world, &self.roots, PrefabError::MissingRoot(*root));
+            return;
+        }
+        if let Some(parent) = self.parent {
+            let Some(mut parent_entity) = world.get_entity_mut(parent) else {
+                fail_spawn_roots(world, &self.roots, PrefabError::MissingParentEntity(parent));
+                for root in self.roots {
+                    if let Some(root) = world.get_entity_mut(root) {
+                        root.despawn_recursive();
+                    }
+                }
+                return;
+            };
+            parent_entity.push_children(&self.roots);
+        }
+
+        let validated = match &self.prefab {
+            PrefabToSpawn::Prepared(prefab) => prefab.debug_validate_forest(),
+            PrefabToSpawn::Unprepared(prefab) => prefab.debug_validate_forest(),
+        };
+        if let Err(error) = validated {
+            fail_spawn_roots(world, &self.roots, error);
+            return;
+        }
+        let mut prefab = match self.prefab {
+            PrefabToSpawn::Prepared(prefab) => prefab,
+            PrefabToSpawn::Unprepared(prefab) => match prefab.try_prepare(world) {
+                Ok(prefab) => prefab,
+                Err((_, error)) => {
+                    fail_spawn_roots(world, &self.roots, error);
+                    return;
+                }
+            },
+        };
+        match prefab.spawn_forest_in(world, &self.roots) {
+            // One event for each root, they all share the same instance
+            Ok(()) => {
+                for root in self.roots {
+                    send_spawn_result(world, root, Ok(root));
+                }
+            }
+            Err(error) => fail_spawn_roots(world, &self.roots, error),
+        }
+    }
+}
+
+/// Extension of [`EntityCommands`] to spawn prefabs in the children of the entity
+pub trait PrefabEntityCommandsExt {
+    /// Spawn the prefab, already prepared, as a child of this entity
+    fn with_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD, Prepared>) -> &mut Self;
+
+    /// Prepare the assets and then Spawn the prefab as a child of this entity
+    fn prepare_with_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> &mut Self;
+}
+
+impl<'w, 's, 'a> PrefabEntityCommandsExt for EntityCommands<'w, 's, 'a> {
+    fn with_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD, Prepared>) -> &mut Self {
+        let parent = self.id();
+        self.commands().spawn_prefab_as_child_of(parent, prefab);
+        self
+    }
+
+    fn prepare_with_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> &mut Self {
+        let parent = self.id();
+        self.commands()
+            .prepare_and_spawn_prefab_as_child_of(parent, prefab);
+        self
+    }
+}
+
+/// Spawn a copy of the shared prefab in the root, that must already exist
+pub struct SpawnPrefabInstance<PD: PrefabData>(Arc<Prefab<PD, Prepared>>, Entity);
+impl<PD: PrefabData + Clone> SpawnPrefabInstance<PD> {
+    pub fn new(prefab: Arc<Prefab<PD, Prepared>>, root: Entity) -> Self {
+        Self(prefab, root)
+    }
+}
+impl<PD: PrefabData + Clone> Command for SpawnPrefabInstance<PD> {
+    fn apply(self, world: &mut World) {
+        if let Err(error) = self.0.debug_validate() {
+            fail_spawn(world, self.1, error);
+            return;
+        }
+        let resultado = self
+            .0
+            .try_instantiate_in(&self.0.all_parents_childs(), world, self.1);
+        send_spawn_result(world, self.1, resultado);
+    }
+}
+
+/// Spawn a copy of the shared prefab in each root, and then insert the transform of the root
+pub struct SpawnPrefabInstances<PD: PrefabData> {
+    prefab: Arc<Prefab<PD, Prepared>>,
+    roots: Vec<(Entity, Transform)>,
+}
+impl<PD: PrefabData + Clone> SpawnPrefabInstances<PD> {
+    /// The roots must already exist
+    pub fn new(prefab: Arc<Prefab<PD, Prepared>>, roots: Vec<(Entity, Transform)>) -> Self {
+        Self { prefab, roots }
+    }
+}
+impl<PD: PrefabData + Clone> Command for SpawnPrefabInstances<PD> {
+    fn apply(self, world: &mut World) {
+        if self.prefab.debug_validate().is_err() {
+            for (root, _) in self.roots {
+                // The error can't be cloned, so it is found again for each root
+                if let Err(error) = self.prefab.debug_validate() {
+                    fail_spawn(world, root, error);
+                }
+            }
+            return;
+        }
+        // The hierarchy is the same for all the copies
+        let processed_children = self.prefab.all_parents_childs();
+        for (root, transform) in self.roots {
+            let resultado = self
+                .prefab
+                .try_instantiate_in(&processed_children, world, root);
+            if resultado.is_ok() {
+                // After the data, so the transform of the prefab is replaced
+                world
+                    .entity_mut(root)
+                    .insert(TransformBundle::from_transform(transform));
+            }
+            send_spawn_result(world, root, resultado);
+        }
+    }
+}
+
+/// This command will consume the prefab after the spawning.
+/// If you want to prepare and retrieve the prefab, use PreparePrefab
+pub struct PrepareAndSpawnPrefab<PD: PrefabData>(Prefab<PD>, Entity);
+impl<PD: PrefabData> PrepareAndSpawnPrefab<PD> {
+    /// The prefab will be spawned in the given root, that must already exist
+    pub fn new(prefab: Prefab<PD>, root: Entity) -> Self {
+        Self(prefab, root)
+    }
+}
+impl<PD: PrefabData> Command for PrepareAndSpawnPrefab<PD> {
+    fn apply(self, world: &mut World) {
+        if let Err(error) = self.0.debug_validate() {
+            fail_spawn(world, self.1, error);
+            return;
+        }
+        match self.0.try_prepare(world) {
+            Ok(mut prefab) => {
+                let resultado =
+                    prefab.spawn_with_root(&prefab.all_parents_childs(), &0, world, self.1);
+                send_spawn_result(world, self.1, resultado);
+            }
+            Err((prefab, error)) => retry_or_fail_spawn(world, self.1, *prefab, error),
+        }
+    }
+}
+
+/// This command will prepare the prefab and wait, in the root, until all the sub-assets are loaded.
+/// The prefab is then spawned by [`spawn_loaded_prefabs`], or a [`FailedPrefab`] event is sent.
+pub struct PrepareAndSpawnPrefabWhenLoaded<PD: PrefabData>(Prefab<PD>, Entity);
+impl<PD: PrefabData> Command for PrepareAndSpawnPrefabWhenLoaded<PD> {
+    fn apply(self, world: &mut World) {
+        if let Err(error) = self.0.debug_validate() {
+            fail_spawn(world, self.1, error);
+            return;
+        }
+        match self.0.try_prepare(world) {
+            Ok(prefab) => match world.get_entity_mut(self.1) {
+                Some(mut root) => {
+                    root.insert(WaitingPrefab::new(prefab, None));
+                }
+                None => fail_spawn(world, self.1, PrefabError::MissingRoot(self.1)),
+            },
+            Err((prefab, error)) => retry_or_fail_spawn(world, self.1, *prefab, error),
+        }
+    }
+}
+
+/// A prefab waiting for its sub-assets in the root entity.
+/// The prefab is still unprepared when its preparation failed, and it is prepared again each frame
+#[derive(Component)]
+pub struct WaitingPrefab<PD: PrefabData> {
+    prefab: PrefabToSpawn<PD>,
+    handles: Vec<UntypedHandle>,
+    /// The frames left to retry, once an asset failed
+    retry: Option<u32>,
+}
+impl<PD: PrefabData> WaitingPrefab<PD> {
+    fn new(prefab: Prefab<PD, Prepared>, retry: Option<u32>) -> Self {
+        let handles = prefab.sub_assets();
+        Self {
+            prefab: PrefabToSpawn::Prepared(prefab),
+            handles,
+            retry,
+        }
+    }
+
+    fn unprepared(prefab: Prefab<PD>, retry: u32) -> Self {
+        Self {
+            prefab: PrefabToSpawn::Unprepared(prefab),
+            handles: Vec::new(),
+            retry: Some(retry),
+        }
+    }
+}
+
+/// Spawn the [`WaitingPrefab`]s which all sub-assets are loaded
+pub fn spawn_loaded_prefabs<PD: PrefabData>(
+    mut commands: Commands,
+    asset_server: Option<Res<AssetServer>>,
+    policy: Option<Res<PrefabFailurePolicy>>,
+    mut waiting: Query<(Entity, &mut WaitingPrefab<PD>)>,
+) {
+    let Some(asset_server) = asset_server else {
+        return;
+    };
+    for (entity, mut waiting_prefab) in waiting.iter_mut() {
+        if let PrefabToSpawn::Unprepared(_) = waiting_prefab.prefab {
+            // The world is needed to prepare it again
+            commands.add(move |world: &mut World| prepare_waiting_prefab::<PD>(world, entity));
+            continue;
+        }
+        let mut loaded = true;
+        let mut failed_assets = Vec::new();
+        for handle in waiting_prefab.handles.iter() {
+            match asset_server.recursive_dependency_load_state(handle.id()) {
+                RecursiveDependencyLoadState::Loaded => {}
+                RecursiveDependencyLoadState::Failed => failed_assets.push(handle.clone()),
+                _ => loaded = false,
+            }
+        }
+
+        if loaded && failed_assets.is_empty() {
+            commands.add(move |world: &mut World| {
+                let Some(mut root) = world.get_entity_mut(entity) else {
+                    return;
+                };
+                let Some(waiting_prefab) = root.take::<WaitingPrefab<PD>>() else {
+                    return;
+                };
+                if let PrefabToSpawn::Prepared(prefab) = waiting_prefab.prefab {
+                    SpawnPrefab(prefab, entity).apply(world);
+                }
+            });
+            continue;
+        }
+
+        // Still loading, only the frames in which an asset failed are counted
+        if failed_assets.is_empty() {
+            continue;
+        }
+        let retry = match waiting_prefab.retry {
+            Some(frames) => frames.checked_sub(1),
+            None => match policy.as_deref() {
+                Some(PrefabFailurePolicy::Retry { frames, .. }) => frames.checked_sub(1),
+                _ => None,
+            },
+        };
+        if let Some(frames) = retry {
+            waiting_prefab.retry = Some(frames);
+            for handle in failed_assets.iter() {
+                if let Some(path) = handle.path() {
+                    asset_server.reload(path.clone());
+                }
+            }
+            continue;
+        }
+
+        commands.entity(entity).remove::<WaitingPrefab<PD>>();
+        // The assets that failed, and the ones still loading
+        let paths = waiting_prefab
+            .handles
+            .iter()
+            .filter(|handle| {
+                asset_server.recursive_dependency_load_state(handle.id())
+                    != RecursiveDependencyLoadState::Loaded
+            })
+            .map(|handle| match handle.path() {
+                Some(path) => path.to_string(),
+                None => format!("{:?}", handle.id()),
+            })
+            .collect();
+        commands.add(move |world: &mut World| {
+            give_up_spawn::<PD>(world, entity, failed_assets, PrefabError::AssetLoad(paths));
+        });
+    }
+}
+
+// Prepare again the prefab waiting in the root, then it waits for its sub-assets like the others
+fn prepare_waiting_prefab<PD: PrefabData>(world: &mut World, root: Entity) {
+    let Some(waiting) = world
+        .get_entity_mut(root)
+        .and_then(|mut entidade| entidade.take::<WaitingPrefab<PD>>())
+    else {
+        return;
+    };
+    let prefab = match waiting.prefab {
+        PrefabToSpawn::Unprepared(prefab) => prefab,
+        PrefabToSpawn::Prepared(_) => {
+            world.entity_mut(root).insert(waiting);
+            return;
+        }
+    };
+    match prefab.try_prepare(world) {
+        Ok(prefab) => {
+            world
+                .entity_mut(root)
+                .insert(WaitingPrefab::new(prefab, waiting.retry));
+        }
+        Err((prefab, error)) => match waiting.retry.and_then(|frames| frames.checked_sub(1)) {
+            Some(frames) => {
+                world
+                    .entity_mut(root)
+                    .insert(WaitingPrefab::unprepared(*prefab, frames));
+            }
+            None => give_up_preparing(world, root, &prefab, error),
+        },
+    }
+}
+
+// The lifecycle events are only sent if they were added to the app, by any `PrefabPlugin`
+fn send_event_if_added<E: Event>(world: &mut World, event: E) {
+    if let Some(mut events) = world.get_resource_mut::<Events<E>>() {
+        events.send(event);
+    }
+}
+
+fn send_spawn_result(world: &mut World, root: Entity, resultado: Result<Entity, PrefabError>) {
+    match resultado {
+        Ok(root) => {
+            let Some(instance) = world.get::<PrefabInstance>(root) else {
+                return;
+            };
+            let event = PrefabSpawned {
+                root,
+                source: instance.source.clone(),
+                entity_map: instance.entities().collect(),
+            };
+            send_event_if_added(world, event);
+        }
+        Err(error) => fail_spawn(world, root, error),
+    }
+}
+
+// Report the error and apply the `PrefabFailurePolicy` to the root
+fn fail_spawn(world: &mut World, root: Entity, error: PrefabError) {
+    fail_spawn_roots(world, &[root], error);
+}
+
+// For forests, the event is sent with the first root and the policy is applied to all of them
+fn fail_spawn_roots(world: &mut World, roots: &[Entity], error: PrefabError) {
+    let Some(root) = roots.first().copied() else {
+        return;
+    };
+    warn!(target: "prefab", "[warn] The prefab couldn't be spawned in {:?}: {}", root, error);
+    send_event_if_added(world, PrefabSpawnFailed { root, error });
+
+    let policy = world
+        .get_resource::<PrefabFailurePolicy>()
+        .cloned()
+        .unwrap_or_default();
+    for root in roots {
+        let Some(mut entidade) = world.get_entity_mut(*root) else {
+            continue;
+        };
+        match policy.on_give_up() {
+            PrefabFailurePolicy::DespawnRoot => entidade.despawn_recursive(),
+            PrefabFailurePolicy::Placeholder(placeholder) => placeholder(&mut entidade),
+            _ => {}
+        }
+    }
+}
+
+// The assets of the prefab couldn't be prepared, prepare it again in the root if the policy retries
+fn retry_or_fail_spawn<PD: PrefabData>(
+    world: &mut World,
+    root: Entity,
+    prefab: Prefab<PD>,
+    error: PrefabError,
+) {
+    let frames = match world.get_resource::<PrefabFailurePolicy>() {
+        Some(PrefabFailurePolicy::Retry { frames, .. }) => *frames,
+        _ => 0,
+    };
+    // Only the assets can succeed on another try
+    if frames > 0 && matches!(error, PrefabError::AssetPrepare(_)) {
+        if let Some(mut entidade) = world.get_entity_mut(root) {
+            entidade.insert(WaitingPrefab::unprepared(prefab, frames));
+            return;
+        }
+    }
+    give_up_preparing(world, root, &prefab, error);
+}
+
+// The failed assets are the ones of the entities that couldn't be prepared
+fn give_up_preparing<PD: PrefabData>(
+    world: &mut World,
+    root: Entity,
+    prefab: &Prefab<PD>,
+    error: PrefabError,
+) {
+    let failed_assets = match &error {
+        PrefabError::AssetPrepare(entities) => prefab.sub_assets_of(entities),
+        _ => Vec::new(),
+    };
+    give_up_spawn::<PD>(world, root, failed_assets, error);
+}
+
+// No more retries, the failure is reported once and the policy is applied to the root
+pub(crate) fn give_up_spawn<PD: PrefabData>(
+    world: &mut World,
+    root: Entity,
+    failed_assets: Vec<UntypedHandle>,
+    error: PrefabError,
+) {
+    send_event_if_added(
+        world,
+        FailedPrefab::<PD> {
+            root,
+            failed_assets,
+            marker: PhantomData,
+        },
+    );
+    fail_spawn(world, root, error);
+}
+
+pub struct PreparePrefab<PD: PrefabData> {
+    prefab: Prefab<PD>,
+    id: PrefabRequestId,
+    /// Keep the prefab in the [`PreparedPrefabs`] instead of sending it
+    store: bool,
+}
+impl<PD: PrefabData> PreparePrefab<PD> {
+    pub fn new(prefab: Prefab<PD>) -> Self {
+        Self {
+            prefab,
+            id: PrefabRequestId::next(),
+            store: false,
+        }
+    }
+
+    /// Keep the prepared prefab in the [`PreparedPrefabs`] resource, instead of a [`LoadedPrefab`] event
+    pub fn stored(mut self) -> Self {
+        self.store = true;
+        self
+    }
+
+    /// The id of the prepared prefab, in the [`LoadedPrefab`] event or the [`PreparedPrefabs`]
+    pub fn id(&self) -> PrefabRequestId {
+        self.id
+    }
+}
+impl<PD: PrefabData> Command for PreparePrefab<PD> {
+    fn apply(self, world: &mut World) {
+        let prefab = self.prefab.try_prepare(world);
+        if self.store {
+            world
+                .get_resource_or_insert_with(PreparedPrefabs::<PD>::default)
+                .prefabs
+                .insert(self.id, prefab);
+            return;
+        }
+        let event = LoadedPrefab {
+            id: self.id,
+            prefab,
         };
-        Ok(content)
+        world.send_event(event);
     }
 }
-pub struct PrefabCommands<'c, 'w, 's, PD: PrefabData> {
-    prefab: Prefab<PD>,
-    commands: &'c mut Commands<'w, 's>,
-}
-impl<'c, 'w, 's, PD: PrefabData> PrefabCommands<'c, 'w, 's, PD> {
-    pub fn get_entity_mut(&mut self, index: usize) -> Option<&mut PrefabEntityBuilder<PD>> {
-        self.prefab.get_entity_mut(index)
-    }
 
-    pub fn get_entity(&self, index: usize) -> Option<&PrefabEntityBuilder<PD>> {
-        self.prefab.get_entity(index)
+/// Identify a prefab given to [`PreparePrefab`], see [`PrefabCommandsExt::prepare_prefab`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PrefabRequestId(u64);
+impl PrefabRequestId {
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
     }
+}
 
-    pub fn add(&mut self, parent: Option<usize>, data: Option<PD>) -> usize {
-        self.prefab.add(parent, data)
+/// The prefabs prepared with [`PrefabCommandsExt::prepare_prefab_stored`], by their id
+#[derive(Resource)]
+pub struct PreparedPrefabs<PD: PrefabData> {
+    prefabs: HashMap<PrefabRequestId, PrepareResult<PD>>,
+}
+impl<PD: PrefabData> PreparedPrefabs<PD> {
+    /// If the preparation of the prefab of the id is done, even if it failed
+    pub fn contains(&self, id: PrefabRequestId) -> bool {
+        self.prefabs.contains_key(&id)
     }
 
-    pub fn spawn<B: Bundle>(self, bundle: B) -> Entity {
-        self.commands.spawn_prefab_with(self.prefab, bundle)
+    /// If all the assets of the prefab were prepared, None if its preparation isn't done
+    pub fn is_prepared(&self, id: PrefabRequestId) -> Option<bool> {
+        self.prefabs.get(&id).map(Result::is_ok)
     }
 
-    pub fn spawn_empty(self) -> Entity {
-        self.commands.spawn_prefab(self.prefab)
+    /// Why the prefab of the id couldn't be prepared
+    pub fn get_error(&self, id: PrefabRequestId) -> Option<&PrefabError> {
+        let (_, error) = self.prefabs.get(&id)?.as_ref().err()?;
+        Some(error)
     }
 
-    pub fn prepare_spawn_empty(self) -> Entity {
-        self.commands.prepare_and_spawn_prefab(self.prefab)
+    /// The prefab of the id, only if it was prepared
+    pub fn get(&self, id: PrefabRequestId) -> Option<&Prefab<PD, Prepared>> {
+        self.prefabs.get(&id)?.as_ref().ok()
     }
 
-    pub fn prepare_spawn<B: Bundle>(self, bundle: B) -> Entity {
-        self.commands
-            .prepare_and_spawn_prefab_with(self.prefab, bundle)
+    pub fn get_mut(&mut self, id: PrefabRequestId) -> Option<&mut Prefab<PD, Prepared>> {
+        self.prefabs.get_mut(&id)?.as_mut().ok()
     }
 
-    pub fn prepare(self) {
-        self.commands.prepare_prefab(self.prefab)
+    /// Take the prefab out of the resource, to spawn it
+    pub fn take(&mut self, id: PrefabRequestId) -> Option<Prefab<PD, Prepared>> {
+        match self.prefabs.remove(&id)? {
+            Ok(prefab) => Some(prefab),
+            // The failed prefab is kept, see `take_result`
+            Err(failed) => {
+                self.prefabs.insert(id, Err(failed));
+                None
+            }
+        }
     }
 
-    pub fn commands(&mut self) -> &mut Commands<'w, 's> {
-        self.commands
+    /// Take the result out of the resource, a failed prefab can be prepared again
+    pub fn take_result(&mut self, id: PrefabRequestId) -> Option<PrepareResult<PD>> {
+        self.prefabs.remove(&id)
     }
-
-    pub fn prefab(self) -> Prefab<PD> {
-        self.prefab
+}
+impl<PD: PrefabData> Default for PreparedPrefabs<PD> {
+    fn default() -> Self {
+        Self {
+            prefabs: HashMap::new(),
+        }
     }
 }
 
-pub trait PrefabCommandsExt<'c, 'w, 's> {
-    /// Load the prefab and create the commands
-    fn load_prefab<PD: PrefabData, F: Format<PD>, S: AsRef<Path>>(
-        &'c mut self,
-        name: S,
-    ) -> PrefabCommands<'c, 'w, 's, PD>;
-
-    /// Spawn an external prefab
-    fn spawn_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> Entity;
-
-    /// Spawn an external prefab with a bundle in the parent
-    fn spawn_prefab_with<PD: PrefabData, B: Bundle>(
-        &mut self,
-        prefab: Prefab<PD>,
-        bundle: B,
-    ) -> Entity;
+/// The file of a root spawned by the commands of [`PrefabCommandsExt::load_prefab`].
+/// With the hot reload of the [`PrefabPlugin`], the root is spawned again when the file changes.
+/// Only the file itself is watched, not its base or the prefabs it references.
+///
+/// A forest keeps the file in its first root, with the other roots
+#[derive(Component)]
+pub struct PrefabFile<PD> {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    load: Arc<dyn Fn() -> Result<Prefab<PD>, PrefabError> + Send + Sync>,
+    forest: Option<Vec<Entity>>,
+}
+impl<PD: PrefabData> PrefabFile<PD> {
+    fn new<F: Format<PD>>(path: &Path) -> Self {
+        let path = path.to_path_buf();
+        let file = path.clone();
+        Self {
+            modified: modified_time(&path),
+            path,
+            load: Arc::new(move || PrefabLoader::create_prefab::<PD, F, _>(&file)),
+            forest: None,
+        }
+    }
 
-    /// Prepare the assets and then Spawn
-    fn prepare_and_spawn_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> Entity;
+    // The arguments are given again to the reloaded prefab
+    fn with_args(&mut self, args: PrefabArgs)
+    where
+        PD: Serialize + DeserializeOwned,
+    {
+        let load = self.load.clone();
+        self.load = Arc::new(move || {
+            let mut prefab = load()?;
+            prefab.apply_args(&args)?;
+            Ok(prefab)
+        });
+    }
 
-    /// Prepare the assets and then Spawn with the given bundle
-    fn prepare_and_spawn_prefab_with<PD: PrefabData, B: Bundle>(
-        &mut self,
-        prefab: Prefab<PD>,
-        bundle: B,
-    ) -> Entity;
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
 
-    /// Take the prefab and will prepare, when loaded, the prefab will be returned as a [`LoadedPrefab`] event
-    fn prepare_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>);
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
 
-    /// Create prefab commands from a external prefab
-    fn prefab_into_commands<PD: PrefabData>(
-        &'c mut self,
-        prefab: Prefab<PD>,
-    ) -> PrefabCommands<'c, 'w, 's, PD>;
+/// How often the [`PrefabFile`]s of PD are checked, see [`PrefabPlugin::with_hot_reload`]
+#[derive(Resource)]
+pub struct PrefabReloadTimer<PD> {
+    pub timer: Timer,
+    marker: PhantomData<PD>,
+}
+impl<PD> PrefabReloadTimer<PD> {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            timer: Timer::new(interval, TimerMode::Repeating),
+            marker: PhantomData,
+        }
+    }
 }
 
-impl<'c, 'w, 's> PrefabCommandsExt<'c, 'w, 's> for Commands<'w, 's> {
-    fn load_prefab<PD: PrefabData, F: Format<PD>, S: AsRef<Path>>(
-        &'c mut self,
-        name: S,
-    ) -> PrefabCommands<'c, 'w, 's, PD> {
-        let prefab = PrefabLoader::create_prefab::<PD, F, S>(name);
-        let prefab = if let Err(e) = prefab {
-            error!(target: "prefab", "[error] Prefab Error: {e}");
-            Default::default()
-        } else {
-            prefab.unwrap()
-        };
-        PrefabCommands {
-            prefab,
-            commands: self,
+/// Spawn again the roots which [`PrefabFile`] changed, added by [`PrefabPlugin::with_hot_reload`].
+/// The files are only checked when the [`PrefabReloadTimer`] finishes
+pub fn reload_prefab_files<PD: PrefabData>(
+    mut commands: Commands,
+    mut files: Query<(Entity, &mut PrefabFile<PD>)>,
+    mut timer: ResMut<PrefabReloadTimer<PD>>,
+    time: Res<Time>,
+) {
+    if !timer.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    // Many roots can share the same file
+    let mut modificados = HashMap::new();
+    for (root, mut file) in files.iter_mut() {
+        let modified = *modificados
+            .entry(file.path.clone())
+            .or_insert_with(|| modified_time(&file.path));
+        if modified.is_none() || modified == file.modified {
+            continue;
+        }
+        file.modified = modified;
+        match ((file.load)(), file.forest.is_some()) {
+            (Ok(prefab), false) => {
+                commands.add(move |world: &mut World| respawn_prefab(world, root, prefab))
+            }
+            (Ok(prefab), true) => {
+                commands.add(move |world: &mut World| respawn_forest(world, root, prefab))
+            }
+            (Err(e), _) => error!(target: "prefab", "[error] Prefab Error: {e}"),
         }
     }
+}
 
-    fn spawn_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> Entity {
-        let entity = self.spawn_empty().id();
+// Replace the descendants of the root by the new prefab, the components of the root are kept
+pub(crate) fn respawn_prefab<PD: PrefabData>(world: &mut World, root: Entity, prefab: Prefab<PD>) {
+    let Some(mut entidade) = world.get_entity_mut(root) else {
+        return;
+    };
+    // A prefab still waiting for its assets would be spawned too
+    entidade.remove::<WaitingPrefab<PD>>();
+    entidade.despawn_descendants();
+    PrepareAndSpawnPrefab::new(prefab, root).apply(world);
+}
 
-        self.add(SpawnPrefab(prefab, entity));
-        entity
+// Like `respawn_prefab`, for every root of the forest.
+// The file can change the number of roots, they are spawned with the parent of the first one
+fn respawn_forest<PD: PrefabData>(world: &mut World, first: Entity, prefab: Prefab<PD>) {
+    let Some(file) = world.get::<PrefabFile<PD>>(first) else {
+        return;
+    };
+    let mut roots = vec![first];
+    roots.extend(file.forest.iter().flatten().copied());
+    roots.retain(|root| world.get_entity(*root).is_some());
+    let total = prefab.roots().count().max(1);
+    for root in roots.split_off(total.min(roots.len())) {
+        despawn_with_children_recursive(world, root);
     }
+    for root in &roots {
+        world.entity_mut(*root).despawn_descendants();
+    }
+    let parent = world.get::<Parent>(first).map(Parent::get);
+    while roots.len() < total {
+        let root = world.spawn_empty().id();
+        if let Some(mut parent) = parent.and_then(|p| world.get_entity_mut(p)) {
+            parent.add_child(root);
+        }
+        roots.push(root);
+    }
+    if let Some(mut file) = world.get_mut::<PrefabFile<PD>>(first) {
+        file.forest = Some(roots[1..].to_vec());
+    }
+    // The roots are already children of the parent
+    SpawnPrefabForest {
+        prefab: PrefabToSpawn::Unprepared(prefab),
+        roots,
+        parent: None,
+    }
+    .apply(world);
+}
 
-    fn spawn_prefab_with<PD: PrefabData, B: Bundle>(
-        &mut self,
-        prefab: Prefab<PD>,
-        bundle: B,
-    ) -> Entity {
-        let entity = self.spawn(bundle).id();
-
-        self.add(SpawnPrefab(prefab, entity.clone()));
-        entity
+/// Added to every entity spawned from a prefab
+#[derive(Component)]
+pub struct PrefabEntity<PD> {
+    /// The index of the entity in the prefab
+    pub index: usize,
+    /// The root of the prefab, with the [`PrefabInstance`]
+    pub root: Entity,
+    marker: PhantomData<PD>,
+}
+impl<PD> PrefabEntity<PD> {
+    pub fn new(index: usize, root: Entity) -> Self {
+        Self {
+            index,
+            root,
+            marker: PhantomData,
+        }
     }
+}
 
-    fn prefab_into_commands<PD: PrefabData>(
-        &'c mut self,
-        prefab: Prefab<PD>,
-    ) -> PrefabCommands<'c, 'w, 's, PD> {
-        PrefabCommands {
-            prefab,
-            commands: self,
+/// Added to the root of a spawned prefab, to find the entity spawned for each index or name of the prefab
+#[derive(Component, Clone, Debug)]
+pub struct PrefabInstance {
+    root: Entity,
+    roots: Vec<Entity>,
+    source: Option<String>,
+    entities: Vec<Option<Entity>>,
+    names: HashMap<String, usize>,
+}
+impl PrefabInstance {
+    fn new(root: Entity, source: Option<String>) -> Self {
+        Self {
+            root,
+            roots: vec![root],
+            source,
+            entities: Vec::new(),
+            names: HashMap::new(),
         }
     }
 
-    fn prepare_and_spawn_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>) -> Entity {
-        let entity = self.spawn_empty().id();
+    fn insert(&mut self, index: usize, entity: Entity) {
+        if self.entities.len() <= index {
+            self.entities.resize(index + 1, None);
+        }
+        self.entities[index] = Some(entity);
+    }
 
-        self.add(PrepareAndSpawnPrefab(prefab, entity.clone()));
-        entity
+    pub fn root(&self) -> Entity {
+        self.root
     }
 
-    fn prepare_and_spawn_prefab_with<PD: PrefabData, B: Bundle>(
-        &mut self,
-        prefab: Prefab<PD>,
-        bundle: B,
-    ) -> Entity {
-        let entity = self.spawn(bundle).id();
+    /// All the roots of the prefab, more than one for a forest.
+    /// Each of them has a copy of the instance
+    pub fn roots(&self) -> &[Entity] {
+        &self.roots
+    }
 
-        self.add(PrepareAndSpawnPrefab(prefab, entity.clone()));
-        entity
+    /// Where the prefab came from, see [`Prefab::get_source`]
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
     }
 
-    fn prepare_prefab<PD: PrefabData>(&mut self, prefab: Prefab<PD>) {
-        self.add(PreparePrefab(prefab));
+    /// The entity spawned for the index of the prefab
+    pub fn entity(&self, index: usize) -> Option<Entity> {
+        self.entities.get(index).copied().flatten()
     }
-}
 
-pub struct SpawnPrefab<PD: PrefabData>(Prefab<PD>, Entity);
-impl<PD: PrefabData> Command for SpawnPrefab<PD> {
-    fn apply(mut self, world: &mut World) {
-        let processed_children = self.0.all_parents_childs();
+    /// The entity spawned for the first entity of the prefab with the name
+    pub fn entity_by_name(&self, name: &str) -> Option<Entity> {
+        self.entity(*self.names.get(name)?)
+    }
 
-        self.0
-            .spawn_with_root(&processed_children, &0, world, self.1)
-            .unwrap();
+    /// All the spawned entities, with their index in the prefab
+    pub fn entities(&self) -> impl Iterator<Item = (usize, Entity)> + '_ {
+        self.entities
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entity)| Some((index, (*entity)?)))
     }
 }
 
-/// This command will consume the prefab after the spawning.
-/// If you want to prepare and retrieve the prefab, use PreparePrefab
-pub struct PrepareAndSpawnPrefab<PD: PrefabData>(Prefab<PD>, Entity);
-impl<PD: PrefabData> Command for PrepareAndSpawnPrefab<PD> {
-    fn apply(self, world: &mut World) {
-        let mut prefab = self.0;
-        let resultado = prefab.prepare_entities(world);
+/// A reference to another entity of the same prefab, written as its index or its name.
+/// Resolved to the spawned entity by [`PrefabRef::resolve`], in [`PrefabData::resolve_refs`].
+///
+/// The indices follow the entities when they move, see [`PrefabRef::remap`].
+/// A name is looked up in the whole prefab, nested prefabs included, and gives the first entity with it
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum PrefabRef {
+    Index(usize),
+    Name(String),
+    /// The spawned entity, with the reference it was resolved from
+    #[serde(skip)]
+    Entity(Entity, Box<PrefabRef>),
+}
+impl PrefabRef {
+    /// Replace the index or name by the entity spawned for it
+    pub fn resolve(&mut self, instance: &PrefabInstance) {
+        let entity = match self {
+            Self::Index(index) => instance.entity(*index),
+            Self::Name(name) => instance.entity_by_name(name),
+            Self::Entity(..) => return,
+        };
+        match entity {
+            Some(entity) => *self = Self::Entity(entity, Box::new(self.clone())),
+            None => warn!(target: "prefab", "[warn] The reference {:?} isn't in the prefab", self),
+        }
+    }
 
-        if resultado {
-            warn!(target: "prefab", "[warn] Not all the prefab assets was loaded")
-        } else {
-            prefab.spawn_with_root(&prefab.all_parents_childs(), &0, world, self.1);
+    /// Give the new index to the reference, called in [`PrefabData::remap_refs`]
+    pub fn remap(&mut self, map: &dyn Fn(usize) -> Option<usize>) {
+        if let Self::Index(index) = self {
+            match map(*index) {
+                Some(novo) => *index = novo,
+                None => warn!(target: "prefab", "[warn] The reference {:?} left the prefab", self),
+            }
         }
     }
-}
 
-pub struct PreparePrefab<PD: PrefabData>(Prefab<PD>);
-impl<PD: PrefabData> Command for PreparePrefab<PD> {
-    fn apply(self, world: &mut World) {
-        let mut prefab = self.0;
-        let resultado = prefab.prepare_entities(world);
-        let event = LoadedPrefab {
-            prefab,
-            succefully_loaded: resultado,
-        };
-        world.send_event(event);
+    /// The referenced entity, None if it wasn't resolved
+    pub fn entity(&self) -> Option<Entity> {
+        match self {
+            Self::Entity(entity, _) => Some(*entity),
+            _ => None,
+        }
+    }
+}
+impl Serialize for PrefabRef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Index(index) => index.serialize(serializer),
+            Self::Name(name) => name.serialize(serializer),
+            // The entity only means something in this world, the reference is written back
+            Self::Entity(_, reference) => reference.serialize(serializer),
+        }
     }
 }
-
-#[derive(Component)]
-pub struct PrefabEntity<PD>(PhantomData<PD>);
 
 #[derive(Default)]
 pub struct PrefabPlugin<PD: PrefabData> {
+    /// How often the files are checked for the hot reload
+    hot_reload: Option<Duration>,
     marker: PhantomData<PD>,
 }
 impl<PD: PrefabData> PrefabPlugin<PD> {
     pub fn new() -> Self {
         Self {
+            hot_reload: None,
             marker: PhantomData::default(),
         }
     }
+
+    /// Spawn again the roots of the prefabs loaded by [`PrefabCommandsExt::load_prefab`]
+    /// when their file changes, see [`PrefabFile`]. The files are checked every second
+    pub fn with_hot_reload(self) -> Self {
+        self.with_hot_reload_every(Duration::from_secs(1))
+    }
+
+    /// Like [`PrefabPlugin::with_hot_reload`], checking the files at the given interval
+    pub fn with_hot_reload_every(mut self, interval: Duration) -> Self {
+        self.hot_reload = Some(interval);
+        self
+    }
 }
 
 impl<PD: PrefabData> Plugin for PrefabPlugin<PD> {
     fn build(&self, app: &mut App) {
-        app.add_event::<LoadedPrefab<PD>>();
+        app.add_event::<LoadedPrefab<PD>>()
+            .add_event::<FailedPrefab<PD>>()
+            .init_resource::<PreparedPrefabs<PD>>()
+            .add_systems(Update, spawn_loaded_prefabs::<PD>);
+        if let Some(interval) = self.hot_reload {
+            app.insert_resource(PrefabReloadTimer::<PD>::new(interval))
+                .add_systems(Update, reload_prefab_files::<PD>);
+        }
+        // Shared by the plugins of all the prefab datas
+        if !app.world.contains_resource::<Events<PrefabSpawned>>() {
+            app.add_event::<PrefabSpawned>()
+                .add_event::<PrefabSpawnFailed>()
+                .add_event::<PrefabDespawned>()
+                .add_systems(Last, send_despawned_prefabs);
+        }
+    }
+}
+
+/// Send a [`PrefabDespawned`] for each despawned root with a [`PrefabInstance`]
+pub fn send_despawned_prefabs(
+    mut removed: RemovedComponents<PrefabInstance>,
+    entities: Query<()>,
+    mut despawned: EventWriter<PrefabDespawned>,
+) {
+    for root in removed.read() {
+        // Only removing the component isn't a despawn
+        if entities.get(root).is_err() {
+            despawned.send(PrefabDespawned { root });
+        }
     }
 }
 
 #[derive(Event)]
 pub struct LoadedPrefab<PD> {
-    pub prefab: Prefab<PD>,
-    pub succefully_loaded: bool,
+    /// The id returned by [`PrefabCommandsExt::prepare_prefab`]
+    pub id: PrefabRequestId,
+    /// The prefab is only [`Prepared`] if all its sub-assets were prepared
+    pub prefab: PrepareResult<PD>,
+}
+impl<PD> LoadedPrefab<PD> {
+    pub fn succefully_loaded(&self) -> bool {
+        self.prefab.is_ok()
+    }
+}
+
+/// Sent when a prefab was spawned in the root, by any of the spawning commands.
+/// A forest sends one for each of its roots
+#[derive(Event, Clone, Debug)]
+pub struct PrefabSpawned {
+    pub root: Entity,
+    /// Where the prefab came from, see [`Prefab::get_source`]
+    pub source: Option<String>,
+    /// The entity spawned for each index of the prefab
+    pub entity_map: HashMap<usize, Entity>,
+}
+
+/// Sent when a prefab couldn't be spawned in the root
+#[derive(Event, Debug)]
+pub struct PrefabSpawnFailed {
+    pub root: Entity,
+    pub error: PrefabError,
+}
+
+/// Sent when the root of a spawned prefab is despawned
+#[derive(Event, Clone, Debug)]
+pub struct PrefabDespawned {
+    pub root: Entity,
+}
+
+/// What the spawning commands do with the root of a prefab that couldn't be spawned,
+/// after sending a [`PrefabSpawnFailed`]. Insert it as a resource, the root is kept by default
+#[derive(Resource, Clone, Default)]
+pub enum PrefabFailurePolicy {
+    /// Keep the root, with the components it was spawned with
+    #[default]
+    KeepRoot,
+    /// Despawn the root and its descendants
+    DespawnRoot,
+    /// Call the function on the root, to insert a placeholder
+    Placeholder(Arc<dyn Fn(&mut EntityWorldMut) + Send + Sync>),
+    /// Reload the assets that failed, for the given frames in which an asset failed.
+    /// A prefab that couldn't be prepared is prepared again each frame.
+    /// This requires the [`PrefabPlugin`], and then the other policy is used.
+    /// Only the prefabs that are prepared by the commands can be retried, except forests,
+    /// and the failed [`PrefabAsset`](crate::asset::PrefabAsset)s are loaded again
+    Retry {
+        frames: u32,
+        then: Box<PrefabFailurePolicy>,
+    },
+}
+impl PrefabFailurePolicy {
+    /// Insert a clone of the bundle in the root
+    pub fn placeholder<B: Bundle + Clone>(bundle: B) -> Self {
+        Self::Placeholder(Arc::new(move |entidade| {
+            entidade.insert(bundle.clone());
+        }))
+    }
+
+    pub fn retry(frames: u32, then: PrefabFailurePolicy) -> Self {
+        Self::Retry {
+            frames,
+            then: Box::new(then),
+        }
+    }
+
+    // The policy once there are no more retries
+    fn on_give_up(&self) -> &Self {
+        match self {
+            Self::Retry { then, .. } => then.on_give_up(),
+            other => other,
+        }
+    }
+}
+
+/// Sent when a prefab prepared by the spawning commands couldn't load its assets,
+/// once the [`PrefabFailurePolicy`] gives up on it.
+/// The root entity is kept, without the prefab, unless the [`PrefabFailurePolicy`] says otherwise.
+#[derive(Event)]
+pub struct FailedPrefab<PD> {
+    pub root: Entity,
+    /// The assets that failed, or the assets of the entities that couldn't be prepared
+    pub failed_assets: Vec<UntypedHandle>,
+    marker: PhantomData<PD>,
 }
 
 pub mod implements {
-    use super::{IntoComponent, PrefabData};
-    use bevy::ecs::world::{EntityWorldMut, World};
+    use super::{ExtractPrefabData, FromComponent, IntoComponent, PrefabData, PrefabInstance};
+    use bevy::{
+        asset::UntypedHandle,
+        ecs::world::{EntityRef, EntityWorldMut, World},
+    };
 
     impl<T> PrefabData for Option<T>
     where
@@ -535,6 +2934,29 @@ pub mod implements {
             };
             false
         }
+        fn collect_sub_assets(&self, handles: &mut Vec<UntypedHandle>) {
+            if let Some(data) = self {
+                data.collect_sub_assets(handles)
+            }
+        }
+        fn resolve_refs(&mut self, instance: &PrefabInstance) {
+            if let Some(data) = self {
+                data.resolve_refs(instance)
+            }
+        }
+        fn remap_refs(&mut self, map: &dyn Fn(usize) -> Option<usize>) {
+            if let Some(data) = self {
+                data.remap_refs(map)
+            }
+        }
+        // None keeps the data of the base
+        fn merge(&mut self, other: Self) {
+            match (self.as_mut(), other) {
+                (Some(data), Some(other)) => data.merge(other),
+                (None, Some(other)) => *self = Some(other),
+                _ => {}
+            }
+        }
     }
 
     impl<T> PrefabData for T
@@ -544,10 +2966,56 @@ pub mod implements {
         fn insert_into_entity(self, entidade: &mut EntityWorldMut) {
             entidade.insert(self.into_component());
         }
+        fn resolve_refs(&mut self, instance: &PrefabInstance) {
+            IntoComponent::resolve_refs(self, instance)
+        }
+        fn remap_refs(&mut self, map: &dyn Fn(usize) -> Option<usize>) {
+            IntoComponent::remap_refs(self, map)
+        }
+    }
+
+    impl<T> ExtractPrefabData for Option<T>
+    where
+        T: ExtractPrefabData,
+    {
+        fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+            Some(T::extract_from_entity(entidade))
+        }
+    }
+
+    impl<T> ExtractPrefabData for T
+    where
+        T: FromComponent,
+    {
+        fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+            entidade.get::<T::Component>().map(T::from_component)
+        }
     }
 
     macro_rules! impl_prefab_data {
-        ( $($ty:ident:$i:tt),* ) => {
+        () => {
+            impl_prefab_data!(@data);
+
+            // No component holds `()`, so an `Option<()>` is extracted as `None`
+            impl ExtractPrefabData for () {
+                fn extract_from_entity(_entidade: &EntityRef) -> Option<Self> {
+                    None
+                }
+            }
+        };
+        ( $($ty:ident:$i:tt),+ ) => {
+            impl_prefab_data!(@data $($ty:$i),+);
+
+            #[allow(unused)]
+            impl<$($ty),*> ExtractPrefabData for ( $( $ty , )* )
+                where $( $ty : ExtractPrefabData ),*
+            {
+                fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+                    Some(( $( $ty::extract_from_entity(entidade)?, )* ))
+                }
+            }
+        };
+        ( @data $($ty:ident:$i:tt),* ) => {
             #[allow(unused)]
             impl<$($ty),*> PrefabData for ( $( $ty , )* )
                 where $( $ty : PrefabData ),*
@@ -573,6 +3041,36 @@ pub mod implements {
                     ret
                 }
 
+                fn collect_sub_assets(
+                    &self, handles: &mut Vec<UntypedHandle>
+                ) {
+                    $(
+                        self.$i.collect_sub_assets(handles);
+                    )*
+                }
+
+                fn resolve_refs(
+                    &mut self, instance: &PrefabInstance
+                ) {
+                    $(
+                        self.$i.resolve_refs(instance);
+                    )*
+                }
+
+                fn remap_refs(
+                    &mut self, map: &dyn Fn(usize) -> Option<usize>
+                ) {
+                    $(
+                        self.$i.remap_refs(map);
+                    )*
+                }
+
+                fn merge(&mut self, other: Self) {
+                    $(
+                        self.$i.merge(other.$i);
+                    )*
+                }
+
             }
         };
     }
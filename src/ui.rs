@@ -6,6 +6,7 @@ use bevy::{
         event::EventWriter,
         query::Changed,
         system::{Commands, Query, Resource, SystemId},
+        world::EntityRef,
     },
     log::{error, warn},
     prelude::{Entity, Event, Plugin, Update},
@@ -15,8 +16,14 @@ use bevy::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    asset::PrefabAssetPlugin,
     components::ui::General,
-    prefab::{Format, Prefab, PrefabCommands, PrefabCommandsExt, PrefabData, PrefabPlugin},
+    error::PrefabError,
+    format::from_ron_bytes,
+    prefab::{
+        ExtractPrefabData, Format, FormatWriter, Prefab, PrefabCommands, PrefabCommandsExt,
+        PrefabData, PrefabEntityBuilder, PrefabPlugin,
+    },
 };
 
 use super::components::ui::{
@@ -30,6 +37,9 @@ where
 {
     /// A box, used for root purposes
     Container {
+        /// Spawned as a `Name`, and used to find the widget in the prefab
+        #[serde(default)]
+        name: Option<String>,
         node: NodeBundlePrefab,
         #[serde(default = "empty_vec")] // Will add no children if required
         children: Vec<Ui<C>>,
@@ -41,6 +51,8 @@ where
 
     /// Simple label with text
     Text {
+        #[serde(default)]
+        name: Option<String>,
         text_data: TextBundlePrefab,
         #[serde(default)]
         custom_data: Option<C::CustomData>,
@@ -48,6 +60,8 @@ where
 
     /// Simple image box
     Image {
+        #[serde(default)]
+        name: Option<String>,
         image_data: ImageBundlePrefab,
         #[serde(default)]
         custom_data: Option<C::CustomData>,
@@ -55,6 +69,8 @@ where
 
     /// A button.
     Button {
+        #[serde(default)]
+        name: Option<String>,
         button: ButtonBundlePrefab,
         #[serde(default)]
         label: Option<TextBundlePrefab>,
@@ -103,17 +119,15 @@ impl CustomWidget for NoCustomWidget {
     }
 }
 
-struct UiFormat<C: CustomWidget>(PhantomData<C>);
+/// The [`Format`] used to load [`Ui`] files into prefabs
+pub struct UiFormat<C: CustomWidget>(PhantomData<C>);
 
 impl<C> Format<UiData<C::CustomData>> for UiFormat<C>
 where
     C: CustomWidget + for<'a> Deserialize<'a>,
 {
-    fn load_from_bytes(
-        bytes: Vec<u8>,
-    ) -> Result<Prefab<UiData<C::CustomData>>, Box<dyn std::error::Error>> {
-        let mut de = ron::de::Deserializer::from_bytes(&bytes)?;
-        let valor = Ui::deserialize(&mut de)?;
+    fn load_from_bytes(bytes: Vec<u8>) -> Result<Prefab<UiData<C::CustomData>>, PrefabError> {
+        let valor: Ui<C> = from_ron_bytes(&bytes)?;
         let mut prefab = Prefab::default();
 
         ui_tree::<C>(valor, 0, &mut prefab);
@@ -121,6 +135,131 @@ where
     }
 }
 
+impl<C> FormatWriter<UiData<C::CustomData>> for UiFormat<C>
+where
+    C: CustomWidget + Serialize,
+    C::CustomData: Clone,
+{
+    /// Custom widgets are written as their native widgets.
+    /// The prefab must not be prepared, loaded assets and systems can't be written.
+    fn save_to_bytes(prefab: &Prefab<UiData<C::CustomData>>) -> Result<Vec<u8>, PrefabError> {
+        let children = prefab.all_parents_childs();
+        let mut visited = vec![false; prefab.len()];
+        let valor = prefab_ui::<C>(prefab, &children, 0, &mut visited)?;
+        if let Some(index) = visited.iter().position(|v| !v) {
+            return Err(PrefabError::Unreachable(index));
+        }
+
+        let ron = ron::ser::to_string_pretty(&valor, ron::ser::PrettyConfig::default())
+            .map_err(PrefabError::Serialize)?;
+        Ok(ron.into_bytes())
+    }
+}
+
+/// Rebuild the widget of the given index, the counterpart of `ui_tree`
+fn prefab_ui<C: CustomWidget>(
+    prefab: &Prefab<UiData<C::CustomData>>,
+    processed_children: &HashMap<usize, Vec<usize>>,
+    index: usize,
+    visited: &mut [bool],
+) -> Result<Ui<C>, PrefabError>
+where
+    C::CustomData: Clone,
+{
+    let Some(entity) = prefab.get_entity(index) else {
+        return Err(PrefabError::MissingEntity(index));
+    };
+    if visited[index] {
+        return Err(PrefabError::Cycle(index));
+    }
+    visited[index] = true;
+
+    let children = processed_children
+        .get(&index)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let name = entity.get_name().map(str::to_string);
+    let data = entity
+        .get_data()
+        .cloned()
+        .unwrap_or((None, None, None, None, None, None, None));
+
+    let widget = match data {
+        (None, Some(text_data), None, None, None, None, custom_data) if children.is_empty() => {
+            Ui::Text {
+                name,
+                text_data,
+                custom_data,
+            }
+        }
+        (None, None, Some(image_data), None, None, None, custom_data) if children.is_empty() => {
+            Ui::Image {
+                name,
+                image_data,
+                custom_data,
+            }
+        }
+        (None, None, None, Some(button), callback, None, custom_data) => {
+            let label = match children {
+                [] => None,
+                [label] => {
+                    visited[*label] = true;
+                    match prefab.get_entity(*label).and_then(|l| l.get_data()) {
+                        // `Option::is_none_or` needs a newer Rust
+                        #[allow(clippy::unnecessary_map_or)]
+                        Some((None, Some(text), None, None, None, None, None))
+                            if processed_children.get(label).map_or(true, Vec::is_empty) =>
+                        {
+                            Some(text.clone())
+                        }
+                        _ => {
+                            return Err(PrefabError::InvalidEntity {
+                                entity: *label,
+                                reason: format!(
+                                    "Must be a simple text to be the label of the button {index}"
+                                ),
+                            })
+                        }
+                    }
+                }
+                _ => {
+                    return Err(PrefabError::InvalidEntity {
+                        entity: index,
+                        reason: "A button can have only the label as child".to_string(),
+                    })
+                }
+            };
+            Ui::Button {
+                name,
+                button,
+                label,
+                callback,
+                custom_data,
+            }
+        }
+        (node, None, None, None, None, other_data, custom_data) => {
+            let mut widgets = Vec::new();
+            for child in children {
+                widgets.push(prefab_ui(prefab, processed_children, *child, visited)?);
+            }
+            Ui::Container {
+                name,
+                node: node.unwrap_or_default(),
+                children: widgets,
+                other_data,
+                custom_data,
+            }
+        }
+        _ => {
+            return Err(PrefabError::InvalidEntity {
+                entity: index,
+                reason: "Can't be written as an Ui widget".to_string(),
+            })
+        }
+    };
+    Ok(widget)
+}
+
 fn ui_tree<C: CustomWidget>(
     widget: Ui<C>,
     index: usize,
@@ -132,33 +271,51 @@ fn ui_tree<C: CustomWidget>(
             ui_tree(widget, index, prefab);
         }
         Ui::Text {
+            name,
             text_data,
             custom_data,
         } => {
-            prefab
-                .get_entity_mut(index)
-                .expect("Unreachable: `Prefab` entity should always be set when walking ui tree")
-                .set_data((None, Some(text_data), None, None, None, None, custom_data));
+            ui_entity(prefab, index, name).set_data((
+                None,
+                Some(text_data),
+                None,
+                None,
+                None,
+                None,
+                custom_data,
+            ));
         }
         Ui::Image {
+            name,
             image_data,
             custom_data,
         } => {
-            prefab
-                .get_entity_mut(index)
-                .expect("Unreachable: `Prefab` entity should always be set when walking ui tree")
-                .set_data((None, None, Some(image_data), None, None, None, custom_data));
+            ui_entity(prefab, index, name).set_data((
+                None,
+                None,
+                Some(image_data),
+                None,
+                None,
+                None,
+                custom_data,
+            ));
         }
         Ui::Button {
+            name,
             button,
             label,
             callback,
             custom_data,
         } => {
-            prefab
-                .get_entity_mut(index)
-                .expect("Unreachable: `Prefab` entity should always be set when walking ui tree")
-                .set_data((None, None, None, Some(button), callback, None, custom_data));
+            ui_entity(prefab, index, name).set_data((
+                None,
+                None,
+                None,
+                Some(button),
+                callback,
+                None,
+                custom_data,
+            ));
 
             if let Some(text) = label {
                 prefab.add(
@@ -168,15 +325,21 @@ fn ui_tree<C: CustomWidget>(
             }
         }
         Ui::Container {
+            name,
             node,
             children,
             custom_data,
             other_data,
         } => {
-            prefab
-                .get_entity_mut(index)
-                .expect("Unreachable: `Prefab` entity should always be set when walking ui tree")
-                .set_data((Some(node), None, None, None, None, other_data, custom_data));
+            ui_entity(prefab, index, name).set_data((
+                Some(node),
+                None,
+                None,
+                None,
+                None,
+                other_data,
+                custom_data,
+            ));
 
             for child in children {
                 let child_index = prefab.add(Some(index), None);
@@ -186,12 +349,42 @@ fn ui_tree<C: CustomWidget>(
     }
 }
 
-pub struct UiPrefabPlugin<C = NoCustomWidget>(PhantomData<C>)
+fn ui_entity<CD>(
+    prefab: &mut Prefab<UiData<CD>>,
+    index: usize,
+    name: Option<String>,
+) -> &mut PrefabEntityBuilder<UiData<CD>> {
+    let entidade = prefab
+        .get_entity_mut(index)
+        .expect("Unreachable: `Prefab` entity should always be set when walking ui tree");
+    if let Some(name) = name {
+        entidade.set_name(name);
+    }
+    entidade
+}
+
+/// Spawn the Ui prefabs, and load the `ui.ron` files as assets.
+///
+/// Add it after the `AssetPlugin` (or the `DefaultPlugins`) to load them with the `AssetServer`,
+/// otherwise only [`CreateUiExt::load_ui`] can be used.
+pub struct UiPrefabPlugin<C = NoCustomWidget>
 where
-    C: CustomWidget;
-impl<C: CustomWidget> Plugin for UiPrefabPlugin<C> {
+    C: CustomWidget,
+{
+    extensions: &'static [&'static str],
+    hot_reload: bool,
+    marker: PhantomData<C>,
+}
+impl<C: CustomWidget + for<'a> Deserialize<'a>> Plugin for UiPrefabPlugin<C> {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_plugins(PrefabPlugin::<UiData<C::CustomData>>::new())
+        let mut prefab_plugin = PrefabPlugin::<UiData<C::CustomData>>::new();
+        if self.hot_reload {
+            prefab_plugin = prefab_plugin.with_hot_reload();
+        }
+        app.add_plugins(prefab_plugin)
+            .add_plugins(
+                PrefabAssetPlugin::<UiData<C::CustomData>, UiFormat<C>>::new(self.extensions),
+            )
             .insert_resource(UiButtonCallbacks::new())
             .add_systems(Update, handle_prefab_events)
             .add_event::<PressedButtonEvent>();
@@ -199,12 +392,28 @@ impl<C: CustomWidget> Plugin for UiPrefabPlugin<C> {
 }
 impl Default for UiPrefabPlugin {
     fn default() -> Self {
-        Self(PhantomData)
+        Self::new()
     }
 }
 impl<C: CustomWidget> UiPrefabPlugin<C> {
     pub fn new() -> Self {
-        Self(Default::default())
+        Self {
+            extensions: &["ui.ron"],
+            hot_reload: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Spawn again the Ui loaded by [`CreateUiExt::load_ui`] when its file changes
+    pub fn with_hot_reload(mut self) -> Self {
+        self.hot_reload = true;
+        self
+    }
+
+    /// Change the extensions of the Ui files loaded by the `AssetServer`, `"ui.ron"` by default
+    pub fn with_extensions(mut self, extensions: &'static [&'static str]) -> Self {
+        self.extensions = extensions;
+        self
     }
 }
 
@@ -360,6 +569,16 @@ impl PrefabData for CallbackPrefab {
         }
     }
 }
+impl ExtractPrefabData for CallbackPrefab {
+    fn extract_from_entity(entidade: &EntityRef) -> Option<Self> {
+        match entidade.get::<CallBack>()? {
+            CallBack::RegisteredSystem(s) => Some(Self::LoadedSystem(*s)),
+            CallBack::EventSender(e) => Some(Self::Event {
+                name: e.button_name.clone(),
+            }),
+        }
+    }
+}
 
 pub fn handle_prefab_events(
     mut commands: Commands,
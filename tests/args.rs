@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Component)]
+struct Title(String);
+#[derive(Deserialize, Serialize)]
+struct TitlePrefab(String);
+impl IntoComponent for TitlePrefab {
+    type Component = Title;
+    fn into_component(self) -> Self::Component {
+        Title(self.0)
+    }
+}
+
+fn prefab(default: Option<&str>) -> Prefab<TitlePrefab> {
+    let mut prefab = Prefab::from_data(Some(TitlePrefab("$title".to_string())));
+    let default = default.map(|default| ArgValue::new(default).unwrap());
+    prefab.declare_param("title", default);
+    prefab.add(Some(0), Some(TitlePrefab("$price".to_string())));
+    prefab
+}
+
+fn spawn(prefab: Prefab<TitlePrefab>, world: &mut World) -> Result<Entity, PrefabError> {
+    let mut prefab = prefab.prepare_entities(world)?;
+    Ok(prefab
+        .spawn(&prefab.all_parents_childs(), &0, world)
+        .unwrap())
+}
+
+#[test]
+fn defaults_are_applied_without_args() {
+    let mut world = World::new();
+    let root = spawn(prefab(Some("Goblin")), &mut world).unwrap();
+
+    assert_eq!(world.get::<Title>(root).unwrap().0, "Goblin");
+    // Not a parameter, the string is kept
+    let child = world.get::<Children>(root).unwrap()[0];
+    assert_eq!(world.get::<Title>(child).unwrap().0, "$price");
+}
+
+#[test]
+fn missing_arg_fails_the_preparation() {
+    let mut world = World::new();
+    let error = spawn(prefab(None), &mut world).unwrap_err();
+    assert!(matches!(error, PrefabError::MissingArg(name) if name == "title"));
+}
+
+#[test]
+fn args_replace_the_defaults() {
+    let mut world = World::new();
+    let mut prefab = prefab(Some("Goblin"));
+    prefab
+        .apply_args(&PrefabArgs::new().with("title", "Boss"))
+        .unwrap();
+    let root = spawn(prefab, &mut world).unwrap();
+    assert_eq!(world.get::<Title>(root).unwrap().0, "Boss");
+}
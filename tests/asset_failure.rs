@@ -0,0 +1,100 @@
+use std::{thread, time::Duration};
+
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Component)]
+struct Value;
+#[derive(Clone, Deserialize, Serialize)]
+struct ValuePrefab;
+impl IntoComponent for ValuePrefab {
+    type Component = Value;
+    fn into_component(self) -> Self::Component {
+        Value
+    }
+}
+
+// The reported failures, read in all the frames
+#[derive(Resource, Default)]
+struct Reported {
+    spawn_failed: Vec<(Entity, bool)>,
+    failed: usize,
+}
+
+fn count_failures(
+    mut spawn_failed: EventReader<PrefabSpawnFailed>,
+    mut failed: EventReader<FailedPrefab<ValuePrefab>>,
+    mut reported: ResMut<Reported>,
+) {
+    for event in spawn_failed.read() {
+        let asset_load = matches!(event.error, PrefabError::AssetLoad(_));
+        reported.spawn_failed.push((event.root, asset_load));
+    }
+    reported.failed += failed.read().count();
+}
+
+fn app(policy: PrefabFailurePolicy) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .add_plugins(PrefabPlugin::<ValuePrefab>::new())
+        .add_plugins(PrefabAssetPlugin::<ValuePrefab, RonPrefabFormat<_>>::new(
+            &["ron"],
+        ))
+        .add_plugins(PrefabAssetSpawnPlugin::<ValuePrefab>::new())
+        .insert_resource(policy)
+        .init_resource::<Reported>()
+        .add_systems(Last, count_failures);
+    let handle: Handle<PrefabAsset<ValuePrefab>> = app
+        .world
+        .resource::<AssetServer>()
+        .load("prefab/missing.ron");
+    let root = app.world.spawn(PrefabAssetInstance::new(handle)).id();
+    (app, root)
+}
+
+// The asset is loaded in another thread
+fn update_until(app: &mut App, done: impl Fn(&App) -> bool) {
+    for _ in 0..500 {
+        app.update();
+        if done(app) {
+            return;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    panic!("Timed out");
+}
+
+fn failed(app: &App) -> bool {
+    app.world.resource::<Reported>().failed > 0
+}
+
+#[test]
+fn failed_asset_is_reported_once() {
+    let (mut app, root) = app(PrefabFailurePolicy::KeepRoot);
+    update_until(&mut app, failed);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let reported = app.world.resource::<Reported>();
+    assert_eq!(reported.failed, 1);
+    assert_eq!(reported.spawn_failed, [(root, true)]);
+    let instance = app.world.get::<PrefabAssetInstance<ValuePrefab>>(root);
+    assert!(instance.unwrap().is_failed());
+    assert!(app.world.get::<Value>(root).is_none());
+}
+
+#[test]
+fn failed_asset_is_reloaded_then_the_policy_is_applied() {
+    let (mut app, root) = app(PrefabFailurePolicy::retry(
+        3,
+        PrefabFailurePolicy::DespawnRoot,
+    ));
+    update_until(&mut app, failed);
+
+    assert!(app.world.get_entity(root).is_none());
+    let reported = app.world.resource::<Reported>();
+    assert_eq!(reported.failed, 1);
+    assert_eq!(reported.spawn_failed, [(root, true)]);
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use bevy_prfb::{
+    sprite::SpriteBundlePrefab,
+    ui::{NoCustomWidget, UiData, UiFormat},
+    *,
+};
+use serde::Serialize;
+
+const MENU: &str = r#"
+Container(
+    name: Some("menu"),
+    node: (
+        style: (width: Px(200.0), padding: All(Px(4.0))),
+        background_color: (Rgba(0.1, 0.2, 0.3, 1.0)),
+    ),
+    children: [
+        Text(
+            name: Some("title"),
+            text_data: (
+                text: (sections: [(text: "Menu", style: (font_size: 30.0))]),
+            ),
+        ),
+        Button(
+            name: Some("play"),
+            button: (border_color: (Rgba(1.0, 0.0, 0.0, 1.0))),
+            label: Some((text: (sections: [(text: "Play")]))),
+            callback: Some(Event(name: "play")),
+        ),
+    ],
+    other_data: Some((text_data: Some((sections: [(text: "v1.0")])), image_data: None)),
+)
+"#;
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+    app
+}
+
+fn ron<T: Serialize>(value: &T) -> String {
+    ron::to_string(value).unwrap()
+}
+
+fn spawn<T: PrefabData + Clone>(prefab: &Prefab<T>, world: &mut World) -> Entity {
+    let mut prepared = prefab.clone().prepare_entities(world).unwrap();
+    let children = prepared.all_parents_childs();
+    prepared.spawn(&children, &0, world).unwrap()
+}
+
+#[test]
+fn captured_ui_matches_the_spawned_prefab() {
+    let mut app = app();
+    let prefab = UiFormat::<NoCustomWidget>::load_from_bytes(MENU.as_bytes().to_vec()).unwrap();
+    let root = spawn(&prefab, &mut app.world);
+
+    let captured: Prefab<UiData> = Prefab::capture(&app.world, root).unwrap();
+    assert_eq!(captured.len(), prefab.len());
+    assert_eq!(captured.all_parents_childs(), prefab.all_parents_childs());
+    for index in 0..prefab.len() {
+        let original = prefab.get_entity(index).unwrap();
+        let entity = captured.get_entity(index).unwrap();
+        assert_eq!(entity.get_name(), original.get_name());
+        assert_eq!(ron(&entity.get_data()), ron(&original.get_data()));
+    }
+
+    // Can be saved again
+    UiFormat::<NoCustomWidget>::save_to_bytes(&captured).unwrap();
+}
+
+#[test]
+fn captured_sprite_matches_the_spawned_prefab() {
+    let mut app = app();
+    let sprite: SpriteBundlePrefab = ron::from_str(
+        "(sprite: (color: Rgba(0.5, 0.5, 0.5, 1.0), flip_x: true), texture: None, \
+         visibility: Hidden)",
+    )
+    .unwrap();
+    let mut prefab = Prefab::from_data(Some(sprite.clone()));
+    prefab.add(Some(0), Some(sprite));
+    let root = spawn(&prefab, &mut app.world);
+
+    let captured: Prefab<SpriteBundlePrefab> = Prefab::capture(&app.world, root).unwrap();
+    assert_eq!(captured.all_parents_childs(), prefab.all_parents_childs());
+    for index in 0..prefab.len() {
+        assert_eq!(
+            ron(&captured.get_entity(index).unwrap().get_data()),
+            ron(&prefab.get_entity(index).unwrap().get_data())
+        );
+    }
+}
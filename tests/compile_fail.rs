@@ -0,0 +1,6 @@
+// The shapes the derives reject, the expected errors are in the `.stderr` files
+#[test]
+fn rejected_derives() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/compile_fail/*.rs");
+}
@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+
+#[derive(PrefabData, ExtractPrefabData)]
+struct EnemyPrefab {
+    #[prefab(bundle)]
+    spatial: SpatialBundle,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+
+fn insert_speed(_speed: f32, _entity: &mut EntityWorldMut) {}
+
+#[derive(PrefabData, ExtractPrefabData)]
+struct EnemyPrefab {
+    #[prefab(with = insert_speed)]
+    speed: f32,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+
+#[derive(Component)]
+struct Enemy;
+
+#[derive(PrefabData)]
+struct EnemyPrefab {
+    #[prefab(marker = Enemy)]
+    note: (),
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+
+#[derive(Component, Prefabable)]
+enum Team {
+    Red,
+    Blue,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+
+#[derive(Component, Prefabable)]
+#[prefabable(skip)]
+struct Health {
+    current: i32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+
+#[derive(Component, Prefabable)]
+struct Health {
+    #[prefabable(component)]
+    current: i32,
+}
+
+fn main() {}
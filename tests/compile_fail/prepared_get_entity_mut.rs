@@ -0,0 +1,8 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+
+fn main() {
+    let mut world = World::new();
+    let mut prefab = Prefab::<()>::new().prepare_entities(&mut world).unwrap();
+    prefab.get_entity_mut(0);
+}
@@ -0,0 +1,13 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+
+#[derive(Component, Clone)]
+struct Team(u8);
+
+#[derive(PrefabData)]
+struct EnemyPrefab {
+    #[prefab(skip, component)]
+    team: Team,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use bevy_prfb::*;
+
+#[derive(PrefabData)]
+union Value {
+    int: i32,
+    float: f32,
+}
+
+fn main() {}
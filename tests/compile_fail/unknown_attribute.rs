@@ -0,0 +1,9 @@
+use bevy_prfb::*;
+
+#[derive(PrefabData)]
+struct EnemyPrefab {
+    #[prefab(default)]
+    note: (),
+}
+
+fn main() {}
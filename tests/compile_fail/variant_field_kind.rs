@@ -0,0 +1,9 @@
+use bevy_prfb::*;
+
+#[derive(PrefabData)]
+enum UnitPrefab {
+    #[prefab(skip)]
+    Idle,
+}
+
+fn main() {}
@@ -0,0 +1,239 @@
+use bevy::{ecs::world::EntityRef, prelude::*};
+use bevy_prfb::{components::ui::UiRectPrefab, *};
+
+#[derive(Component)]
+struct Health(u32);
+#[derive(Debug, PartialEq)]
+struct HealthPrefab(u32);
+impl IntoComponent for HealthPrefab {
+    type Component = Health;
+    fn into_component(self) -> Self::Component {
+        Health(self.0)
+    }
+}
+impl FromComponent for HealthPrefab {
+    fn from_component(component: &Self::Component) -> Self {
+        Self(component.0)
+    }
+}
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Team(u8);
+#[derive(Component)]
+struct Speed(f32);
+#[derive(Component)]
+struct Enemy;
+#[derive(Component)]
+struct Dummy;
+
+fn insert_speed(speed: f32, entity: &mut EntityWorldMut) {
+    entity.insert(Speed(speed));
+}
+fn extract_speed(entity: &EntityRef) -> Option<f32> {
+    entity.get::<Speed>().map(|speed| speed.0)
+}
+
+#[derive(PrefabData, ExtractPrefabData, Debug, PartialEq)]
+#[prefab(marker = Enemy)]
+struct EnemyPrefab {
+    health: HealthPrefab,
+    #[prefab(skip)]
+    note: String,
+    #[prefab(component)]
+    team: Team,
+    #[prefab(with = insert_speed, extract = extract_speed)]
+    speed: f32,
+}
+
+#[derive(PrefabData, ExtractPrefabData, Debug, PartialEq)]
+enum UnitPrefab {
+    #[prefab(marker = Dummy)]
+    Dummy,
+    Melee {
+        health: HealthPrefab,
+    },
+    Idle,
+}
+
+fn spawn<T: PrefabData>(world: &mut World, data: T) -> Entity {
+    let mut entity = world.spawn_empty();
+    data.insert_into_entity(&mut entity);
+    entity.id()
+}
+
+#[test]
+fn derived_struct_inserts_its_fields_and_marker() {
+    let mut world = World::new();
+    let enemy = EnemyPrefab {
+        health: HealthPrefab(10),
+        note: "boss".to_string(),
+        team: Team(2),
+        speed: 1.5,
+    };
+    let mut prefab = Prefab::from_data(Some(enemy))
+        .prepare_entities(&mut world)
+        .unwrap();
+    let entity = prefab
+        .spawn(&prefab.all_parents_childs(), &0, &mut world)
+        .unwrap();
+
+    let entity = world.entity(entity);
+    assert!(entity.contains::<Enemy>());
+    assert_eq!(entity.get::<Health>().unwrap().0, 10);
+    assert_eq!(entity.get::<Team>(), Some(&Team(2)));
+    assert_eq!(entity.get::<Speed>().unwrap().0, 1.5);
+}
+
+#[test]
+fn extracted_struct_reads_its_field_attributes() {
+    let mut world = World::new();
+    let enemy = EnemyPrefab {
+        health: HealthPrefab(10),
+        note: "boss".to_string(),
+        team: Team(2),
+        speed: 1.5,
+    };
+    let entity = spawn(&mut world, enemy);
+
+    let extracted = EnemyPrefab::extract_from_entity(&world.entity(entity)).unwrap();
+    assert_eq!(
+        extracted,
+        EnemyPrefab {
+            health: HealthPrefab(10),
+            // Never inserted, so it's the default
+            note: String::new(),
+            team: Team(2),
+            speed: 1.5,
+        }
+    );
+}
+
+#[test]
+fn extracted_struct_needs_its_marker() {
+    let mut world = World::new();
+    let entity = world.spawn((Health(10), Team(2), Speed(1.5))).id();
+    assert_eq!(
+        EnemyPrefab::extract_from_entity(&world.entity(entity)),
+        None
+    );
+}
+
+#[test]
+fn derived_enum_inserts_the_fields_of_its_variant() {
+    let mut world = World::new();
+    let mut prefab = Prefab::from_data(Some(UnitPrefab::Melee {
+        health: HealthPrefab(3),
+    }));
+    prefab.add(Some(0), Some(UnitPrefab::Dummy));
+    prefab.add(Some(0), Some(UnitPrefab::Idle));
+    let mut prefab = prefab.prepare_entities(&mut world).unwrap();
+    let melee = prefab
+        .spawn(&prefab.all_parents_childs(), &0, &mut world)
+        .unwrap();
+    let children = world.get::<Children>(melee).unwrap();
+    let (dummy, idle) = (children[0], children[1]);
+
+    assert_eq!(world.get::<Health>(melee).unwrap().0, 3);
+    assert!(!world.entity(melee).contains::<Dummy>());
+    assert!(world.entity(dummy).contains::<Dummy>());
+    assert!(!world.entity(dummy).contains::<Health>());
+    assert!(!world.entity(idle).contains::<Dummy>());
+    assert!(!world.entity(idle).contains::<Health>());
+}
+
+#[test]
+fn extracted_enum_is_the_first_variant_found() {
+    let mut world = World::new();
+    let dummy = spawn(&mut world, UnitPrefab::Dummy);
+    let melee = spawn(
+        &mut world,
+        UnitPrefab::Melee {
+            health: HealthPrefab(3),
+        },
+    );
+    let idle = spawn(&mut world, UnitPrefab::Idle);
+
+    let extract = |entity| UnitPrefab::extract_from_entity(&world.entity(entity));
+    assert_eq!(extract(dummy), Some(UnitPrefab::Dummy));
+    assert_eq!(
+        extract(melee),
+        Some(UnitPrefab::Melee {
+            health: HealthPrefab(3)
+        })
+    );
+    assert_eq!(extract(idle), Some(UnitPrefab::Idle));
+}
+
+#[derive(Component, Prefabable, Debug, PartialEq)]
+#[prefabable(default)]
+struct Frame {
+    #[prefabable(mirror = UiRectPrefab)]
+    margin: UiRect,
+    label: String,
+    #[prefabable(skip)]
+    hovered: bool,
+}
+impl Default for Frame {
+    fn default() -> Self {
+        Self {
+            margin: UiRect::all(Val::Px(2.)),
+            label: "frame".to_string(),
+            hovered: false,
+        }
+    }
+}
+
+#[test]
+fn prefabable_mirror_is_read_back_from_the_component() {
+    let mut world = World::new();
+    let frame = Frame {
+        margin: UiRect::horizontal(Val::Px(4.)),
+        label: "menu".to_string(),
+        hovered: true,
+    };
+    let entity = world.spawn(frame).id();
+
+    let mirror = FramePrefab::extract_from_entity(&world.entity(entity)).unwrap();
+    assert_eq!(
+        mirror.into_component(),
+        Frame {
+            margin: UiRect::horizontal(Val::Px(4.)),
+            label: "menu".to_string(),
+            // Not in the mirror
+            hovered: false,
+        }
+    );
+    assert_eq!(FramePrefab::default().into_component(), Frame::default());
+}
+
+// `#[prefab(..)]` and `#[prefabable(..)]` don't get in the way of each other
+#[derive(PrefabData)]
+#[prefab(marker = Enemy)]
+struct PanelPrefab {
+    frame: FramePrefab,
+    #[prefab(skip)]
+    note: String,
+}
+
+#[test]
+fn prefabable_mirror_is_spawned_as_its_component() {
+    let mut world = World::new();
+    let panel = PanelPrefab {
+        frame: FramePrefab {
+            label: "menu".to_string(),
+            ..Default::default()
+        },
+        note: "main".to_string(),
+    };
+    let entity = spawn(&mut world, panel);
+
+    let entity = world.entity(entity);
+    assert!(entity.contains::<Enemy>());
+    assert_eq!(
+        entity.get::<Frame>(),
+        Some(&Frame {
+            label: "menu".to_string(),
+            ..Default::default()
+        })
+    );
+}
@@ -0,0 +1,19 @@
+use std::path::Path;
+
+use bevy_prfb::*;
+
+#[test]
+fn error_without_position_shows_none() {
+    let error = PrefabError::from(ron::Error::Eof);
+    assert_eq!(error.to_string(), ron::Error::Eof.to_string());
+
+    let error = error.with_source(Path::new("enemy.ron"), b"(");
+    assert_eq!(error.to_string(), format!("enemy.ron: {}", ron::Error::Eof));
+}
+
+#[test]
+fn parse_error_shows_its_position() {
+    let error = PrefabError::from(ron::from_str::<u32>("x").unwrap_err());
+    let error = error.with_source(Path::new("enemy.ron"), b"x");
+    assert!(error.to_string().starts_with("enemy.ron:1:1: "));
+}
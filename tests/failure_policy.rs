@@ -0,0 +1,191 @@
+use bevy::{
+    asset::UntypedHandle,
+    ecs::system::{Command, CommandQueue},
+    prelude::*,
+};
+use bevy_prfb::*;
+
+// The preparation of the data fails while there are failures left
+#[derive(Resource)]
+struct Failures(u32);
+
+// The `FailedPrefab` events read in all the frames
+#[derive(Resource, Default)]
+struct Reported(usize);
+
+fn count_failed(
+    mut events: EventReader<FailedPrefab<FlakyPrefab>>,
+    mut reported: ResMut<Reported>,
+) {
+    reported.0 += events.read().count();
+}
+
+#[derive(Component)]
+struct Loaded;
+
+struct FlakyPrefab;
+impl PrefabData for FlakyPrefab {
+    fn insert_into_entity(self, entity: &mut EntityWorldMut) {
+        entity.insert(Loaded);
+    }
+    fn load_sub_assets(&mut self, world: &mut World) -> bool {
+        let mut failures = world.resource_mut::<Failures>();
+        let failed = failures.0 > 0;
+        failures.0 = failures.0.saturating_sub(1);
+        failed
+    }
+}
+
+fn app(failures: u32, policy: PrefabFailurePolicy) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .add_plugins(PrefabPlugin::<FlakyPrefab>::new())
+        .insert_resource(Failures(failures))
+        .insert_resource(policy)
+        .init_resource::<Reported>()
+        .add_systems(Last, count_failed);
+    app
+}
+
+fn spawn(app: &mut App) -> Entity {
+    let root = app.world.spawn_empty().id();
+    let prefab = Prefab::from_data(Some(FlakyPrefab));
+    PrepareAndSpawnPrefab::new(prefab, root).apply(&mut app.world);
+    root
+}
+
+fn failed_events(app: &App) -> usize {
+    app.world.resource::<Reported>().0
+}
+
+#[test]
+fn retry_prepares_again_until_it_succeeds() {
+    let mut app = app(
+        2,
+        PrefabFailurePolicy::retry(5, PrefabFailurePolicy::DespawnRoot),
+    );
+    let root = spawn(&mut app);
+    for _ in 0..4 {
+        app.update();
+    }
+
+    assert!(app.world.get::<Loaded>(root).is_some());
+    assert!(app.world.get::<PrefabInstance>(root).is_some());
+    assert_eq!(failed_events(&app), 0);
+}
+
+#[test]
+fn retry_gives_up_once() {
+    let mut app = app(
+        100,
+        PrefabFailurePolicy::retry(3, PrefabFailurePolicy::DespawnRoot),
+    );
+    let root = spawn(&mut app);
+    for _ in 0..6 {
+        app.update();
+    }
+
+    assert!(app.world.get_entity(root).is_none());
+    assert_eq!(failed_events(&app), 1);
+}
+
+#[test]
+fn without_retry_the_failure_is_reported_once() {
+    let mut app = app(1, PrefabFailurePolicy::KeepRoot);
+    let root = spawn(&mut app);
+    app.update();
+
+    assert!(app.world.get::<Loaded>(root).is_none());
+    assert_eq!(failed_events(&app), 1);
+}
+
+#[test]
+fn stored_preparation_keeps_the_failure() {
+    let mut app = app(1, PrefabFailurePolicy::KeepRoot);
+    let failed = PreparePrefab::new(Prefab::from_data(Some(FlakyPrefab))).stored();
+    let failed_id = failed.id();
+    failed.apply(&mut app.world);
+    let prepared = PreparePrefab::new(Prefab::from_data(Some(FlakyPrefab))).stored();
+    let prepared_id = prepared.id();
+    prepared.apply(&mut app.world);
+
+    let mut prefabs = app.world.resource_mut::<PreparedPrefabs<FlakyPrefab>>();
+    assert_eq!(prefabs.is_prepared(failed_id), Some(false));
+    assert!(prefabs.get_error(failed_id).is_some());
+    assert!(prefabs.take(failed_id).is_none());
+    assert!(prefabs.take_result(failed_id).unwrap().is_err());
+    assert_eq!(prefabs.is_prepared(prepared_id), Some(true));
+    assert!(prefabs.take(prepared_id).is_some());
+}
+
+// The roots are despawned before the command runs
+fn spawn_despawned(app: &mut App, forest: bool) -> Entity {
+    let parent = app.world.spawn_empty().id();
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &app.world);
+    let mut prefab = Prefab::from_data(Some(FlakyPrefab));
+    let roots = match forest {
+        true => {
+            prefab.add(None, Some(FlakyPrefab));
+            commands.prepare_and_spawn_prefab_forest_as_children_of(parent, prefab)
+        }
+        false => vec![commands.prepare_and_spawn_prefab_as_child_of(parent, prefab)],
+    };
+    let mut despawn = CommandQueue::default();
+    let mut commands = Commands::new(&mut despawn, &app.world);
+    for root in roots {
+        commands.entity(root).despawn();
+    }
+    despawn.apply(&mut app.world);
+    queue.apply(&mut app.world);
+    parent
+}
+
+#[test]
+fn missing_root_is_a_failure() {
+    let mut app = app(0, PrefabFailurePolicy::KeepRoot);
+    for forest in [false, true] {
+        let parent = spawn_despawned(&mut app, forest);
+        assert!(app.world.get::<Children>(parent).is_none());
+    }
+}
+
+#[derive(Asset, TypePath)]
+struct Slow;
+
+// Prepared on its second try, then waits for an asset that is never loaded
+struct SlowPrefab(Handle<Slow>);
+impl PrefabData for SlowPrefab {
+    fn insert_into_entity(self, entity: &mut EntityWorldMut) {
+        entity.insert(Loaded);
+    }
+    fn load_sub_assets(&mut self, world: &mut World) -> bool {
+        FlakyPrefab.load_sub_assets(world)
+    }
+    fn collect_sub_assets(&self, handles: &mut Vec<UntypedHandle>) {
+        handles.push(self.0.clone().untyped());
+    }
+}
+
+#[test]
+fn retry_frames_are_not_counted_while_loading() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .add_plugins(PrefabPlugin::<SlowPrefab>::new())
+        .init_asset::<Slow>()
+        .insert_resource(Failures(1))
+        .insert_resource(PrefabFailurePolicy::retry(
+            3,
+            PrefabFailurePolicy::DespawnRoot,
+        ));
+    let root = app.world.spawn_empty().id();
+    let prefab = Prefab::from_data(Some(SlowPrefab(Handle::weak_from_u128(1))));
+    PrepareAndSpawnPrefab::new(prefab, root).apply(&mut app.world);
+    for _ in 0..10 {
+        app.update();
+    }
+
+    // Still waiting
+    assert!(app.world.get::<WaitingPrefab<SlowPrefab>>(root).is_some());
+    assert!(app.world.get::<Loaded>(root).is_none());
+}
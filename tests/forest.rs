@@ -0,0 +1,37 @@
+use bevy::{
+    ecs::system::{CommandQueue, RunSystemOnce},
+    prelude::*,
+};
+use bevy_prfb::*;
+
+struct Empty;
+impl PrefabData for Empty {
+    fn insert_into_entity(self, _: &mut EntityWorldMut) {}
+}
+
+#[test]
+fn every_root_of_a_forest_is_reported() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(PrefabPlugin::<Empty>::new());
+    let mut prefab = Prefab::from_data(Some(Empty));
+    prefab.add(Some(0), None);
+    prefab.add(None, Some(Empty));
+    let prefab = prefab.prepare_entities(&mut app.world).unwrap();
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &app.world);
+    let roots = commands.spawn_prefab_forest(prefab);
+    queue.apply(&mut app.world);
+
+    let spawned = app
+        .world
+        .run_system_once(|mut events: EventReader<PrefabSpawned>| {
+            events
+                .read()
+                .map(|event| (event.root, event.entity_map.len()))
+                .collect::<Vec<_>>()
+        });
+    assert_eq!(roots.len(), 2);
+    assert_eq!(spawned, [(roots[0], 3), (roots[1], 3)]);
+}
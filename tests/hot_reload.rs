@@ -0,0 +1,103 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Component)]
+struct Value(i32);
+#[derive(Deserialize, Serialize)]
+struct ValuePrefab(i32);
+impl IntoComponent for ValuePrefab {
+    type Component = Value;
+    fn into_component(self) -> Self::Component {
+        Value(self.0)
+    }
+}
+
+// The first root has a child, the others are alone
+fn save(path: &Path, roots: &[i32], child: i32, modified: SystemTime) {
+    let mut prefab = Prefab::from_data(Some(ValuePrefab(roots[0])));
+    prefab.add(Some(0), Some(ValuePrefab(child)));
+    for root in &roots[1..] {
+        prefab.add(None, Some(ValuePrefab(*root)));
+    }
+    PrefabLoader::save_prefab::<_, RonPrefabFormat<_>, _>(&prefab, path).unwrap();
+    let file = fs::File::options().write(true).open(path).unwrap();
+    file.set_modified(modified).unwrap();
+}
+
+fn app(path: &Path, forest: bool) -> App {
+    let mut app = App::new();
+    let file = path.to_path_buf();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(PrefabPlugin::<ValuePrefab>::new().with_hot_reload_every(Duration::ZERO))
+        .add_systems(Startup, move |mut commands: Commands| {
+            let prefab = commands.load_prefab::<ValuePrefab, RonPrefabFormat<_>, _>(&file);
+            if forest {
+                prefab.prepare_spawn_forest();
+            } else {
+                prefab.prepare_spawn_empty();
+            }
+        });
+    app.update();
+    app
+}
+
+fn values(app: &mut App) -> Vec<i32> {
+    let mut values: Vec<_> = app
+        .world
+        .query::<&Value>()
+        .iter(&app.world)
+        .map(|v| v.0)
+        .collect();
+    values.sort();
+    values
+}
+
+#[test]
+fn loaded_prefab_is_spawned_again_when_its_file_changes() {
+    let path = std::env::temp_dir().join("bevy_prfb_hot_reload.ron");
+    let now = SystemTime::now();
+    save(&path, &[1], 10, now);
+
+    let mut app = app(&path, false);
+    let root = app
+        .world
+        .query_filtered::<Entity, With<PrefabFile<ValuePrefab>>>()
+        .single(&app.world);
+    assert_eq!(app.world.get::<Value>(root).unwrap().0, 1);
+
+    save(&path, &[2], 20, now + Duration::from_secs(10));
+    app.update();
+    app.update();
+
+    assert_eq!(app.world.get::<Value>(root).unwrap().0, 2);
+    assert_eq!(values(&mut app), vec![2, 20]);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn loaded_forest_follows_its_roots() {
+    let path = std::env::temp_dir().join("bevy_prfb_hot_reload_forest.ron");
+    let now = SystemTime::now();
+    save(&path, &[1, 5], 10, now);
+
+    let mut app = app(&path, true);
+    assert_eq!(values(&mut app), vec![1, 5, 10]);
+
+    save(&path, &[2, 6, 7], 20, now + Duration::from_secs(10));
+    app.update();
+    app.update();
+    assert_eq!(values(&mut app), vec![2, 6, 7, 20]);
+
+    save(&path, &[3], 30, now + Duration::from_secs(20));
+    app.update();
+    app.update();
+    assert_eq!(values(&mut app), vec![3, 30]);
+    fs::remove_file(path).unwrap();
+}
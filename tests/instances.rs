@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Component)]
+struct Value(i32);
+#[derive(Clone, Deserialize, Serialize)]
+struct ValuePrefab(i32);
+impl IntoComponent for ValuePrefab {
+    type Component = Value;
+    fn into_component(self) -> Self::Component {
+        Value(self.0)
+    }
+}
+
+#[derive(Resource)]
+struct Shared(Arc<Prefab<ValuePrefab, Prepared>>);
+
+fn prepared(world: &mut World) -> Prefab<ValuePrefab, Prepared> {
+    let mut prefab = Prefab::from_data(Some(ValuePrefab(1)));
+    prefab.add(Some(0), Some(ValuePrefab(2)));
+    prefab.prepare_entities(world).unwrap()
+}
+
+fn child_value(world: &World, root: Entity) -> i32 {
+    let child = world.get::<Children>(root).unwrap()[0];
+    world.get::<Value>(child).unwrap().0
+}
+
+#[test]
+fn instantiate_keeps_the_prefab() {
+    let mut world = World::new();
+    let prefab = prepared(&mut world);
+
+    let first = prefab.instantiate(&mut world).unwrap();
+    let second = prefab.instantiate(&mut world).unwrap();
+    assert_ne!(first, second);
+    for root in [first, second] {
+        assert_eq!(world.get::<Value>(root).unwrap().0, 1);
+        assert_eq!(child_value(&world, root), 2);
+    }
+    assert!(prefab.get_entity(0).unwrap().get_data().is_some());
+}
+
+#[test]
+fn many_instances_share_the_prepared_prefab() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(PrefabPlugin::<ValuePrefab>::new());
+    let prefab = prepared(&mut app.world);
+    app.insert_resource(Shared(Arc::new(prefab)));
+
+    let xs = [0., 10., 20.];
+    let roots = app
+        .world
+        .run_system_once(move |mut commands: Commands, shared: Res<Shared>| {
+            let transforms = xs.map(|x| Transform::from_xyz(x, 0., 0.));
+            commands.spawn_prefab_instances(&shared.0, transforms)
+        });
+    app.update();
+
+    assert_eq!(roots.len(), 3);
+    for (root, x) in roots.into_iter().zip(xs) {
+        assert_eq!(app.world.get::<Value>(root).unwrap().0, 1);
+        assert_eq!(child_value(&app.world, root), 2);
+        assert_eq!(app.world.get::<Transform>(root).unwrap().translation.x, x);
+    }
+    // Still shared, and still holding its data
+    let shared = &app.world.resource::<Shared>().0;
+    assert_eq!(Arc::strong_count(shared), 1);
+    assert!(shared.get_entity(1).unwrap().get_data().is_some());
+}
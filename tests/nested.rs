@@ -0,0 +1,94 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+// Only the prefab is checked, the component is never spawned
+#[allow(dead_code)]
+#[derive(Component)]
+struct Value(i32);
+#[derive(Deserialize, Serialize)]
+struct ValuePrefab(i32);
+impl IntoComponent for ValuePrefab {
+    type Component = Value;
+    fn into_component(self) -> Self::Component {
+        Value(self.0)
+    }
+}
+
+// Each test writes its files in its own folder
+fn write(dir: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (name, content) in files {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+fn load(path: PathBuf) -> Result<Prefab<ValuePrefab>, PrefabError> {
+    PrefabLoader::create_prefab::<ValuePrefab, RonPrefabFormat<_>, _>(path)
+}
+
+fn data(prefab: &Prefab<ValuePrefab>, index: usize) -> Option<i32> {
+    prefab.get_entity(index)?.get_data().map(|data| data.0)
+}
+
+#[test]
+fn nested_references_are_grafted_under_their_entity() {
+    let dir = write(
+        "bevy_prfb_nested",
+        &[
+            (
+                "house.ron",
+                r#"(data: Some((1)), children: [(name: Some("door"), prefab: Some("doors/door.ron"))])"#,
+            ),
+            (
+                "doors/door.ron",
+                r#"(data: Some((2)), children: [(prefab: Some("knob.ron"))])"#,
+            ),
+            ("doors/knob.ron", r#"(data: Some((3)))"#),
+        ],
+    );
+
+    let prefab = load(dir.join("house.ron")).unwrap();
+    assert!(!prefab.has_references());
+    assert_eq!(prefab.len(), 5);
+    // The root of the referenced prefab is a child of the referencing entity
+    let door = prefab.index_by_name("door").unwrap();
+    let door: Vec<_> = prefab.children_of(door).collect();
+    assert_eq!(door, [2]);
+    assert_eq!(data(&prefab, 2), Some(2));
+    // The references of the referenced prefab are resolved relative to its own file
+    assert_eq!(prefab.parent_of(3), Some(2));
+    assert_eq!(prefab.parent_of(4), Some(3));
+    assert_eq!(data(&prefab, 4), Some(3));
+}
+
+#[test]
+fn reference_cycle_is_an_error() {
+    let dir = write(
+        "bevy_prfb_reference_cycle",
+        &[
+            ("a.ron", r#"(children: [(prefab: Some("b.ron"))])"#),
+            ("b.ron", r#"(children: [(prefab: Some("a.ron"))])"#),
+        ],
+    );
+
+    let result = load(dir.join("a.ron"));
+    assert!(matches!(result, Err(PrefabError::ReferenceCycle(path)) if path.ends_with("a.ron")));
+}
+
+#[test]
+fn self_reference_is_an_error() {
+    let dir = write(
+        "bevy_prfb_self_reference",
+        &[("a.ron", r#"(children: [(prefab: Some("a.ron"))])"#)],
+    );
+
+    let result = load(dir.join("a.ron"));
+    assert!(matches!(result, Err(PrefabError::ReferenceCycle(_))));
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Component)]
+struct Aim(PrefabRef);
+#[derive(Deserialize, Serialize)]
+struct AimPrefab(PrefabRef);
+impl IntoComponent for AimPrefab {
+    type Component = Aim;
+    fn into_component(self) -> Self::Component {
+        Aim(self.0)
+    }
+    fn resolve_refs(&mut self, instance: &PrefabInstance) {
+        self.0.resolve(instance);
+    }
+    fn remap_refs(&mut self, map: &dyn Fn(usize) -> Option<usize>) {
+        self.0.remap(map);
+    }
+}
+
+fn aim(prefab: &Prefab<Option<AimPrefab>>, index: usize) -> Option<&PrefabRef> {
+    prefab
+        .get_entity(index)?
+        .get_data()?
+        .as_ref()
+        .map(|aim| &aim.0)
+}
+
+// A root aiming at its only child
+fn turret() -> Prefab<Option<AimPrefab>> {
+    let mut prefab = Prefab::from_data(Some(Some(AimPrefab(PrefabRef::Index(1)))));
+    prefab.add(Some(0), None);
+    prefab
+}
+
+#[test]
+fn resolved_ref_is_serialized_as_its_reference() {
+    let mut world = World::new();
+    let mut prefab = Prefab::from_data(Some(AimPrefab(PrefabRef::Name("target".to_string()))));
+    let target = prefab.add(Some(0), None);
+    prefab.get_entity_mut(target).unwrap().set_name("target");
+    let mut prefab = prefab.prepare_entities(&mut world).unwrap();
+    let root = prefab
+        .spawn(&prefab.all_parents_childs(), &0, &mut world)
+        .unwrap();
+
+    let aim = &world.get::<Aim>(root).unwrap().0;
+    let children = world.get::<Children>(root).unwrap();
+    assert_eq!(aim.entity(), Some(children[0]));
+
+    let text = ron::to_string(aim).unwrap();
+    assert_eq!(text, "\"target\"");
+    let reference: PrefabRef = ron::from_str(&text).unwrap();
+    assert_eq!(reference, PrefabRef::Name("target".to_string()));
+}
+
+#[test]
+fn grafted_index_ref_follows_its_entity() {
+    let mut world = World::new();
+    let mut prefab = Prefab::from_data(None);
+    prefab.add(Some(0), None);
+    let grafted = prefab.graft(1, turret());
+    assert_eq!(aim(&prefab, grafted), Some(&PrefabRef::Index(grafted + 1)));
+
+    let mut prefab = prefab.prepare_entities(&mut world).unwrap();
+    let root = prefab
+        .spawn(&prefab.all_parents_childs(), &0, &mut world)
+        .unwrap();
+    let instance = world.get::<PrefabInstance>(root).unwrap();
+    let (turret, target) = (instance.entity(grafted), instance.entity(grafted + 1));
+    assert_eq!(
+        world.get::<Aim>(turret.unwrap()).unwrap().0.entity(),
+        target
+    );
+}
+
+#[test]
+fn removed_subtree_keeps_its_index_refs() {
+    let mut prefab = Prefab::from_data(None);
+    prefab.add(Some(0), None);
+    let turret = prefab.graft(0, turret());
+    prefab
+        .get_entity_mut(0)
+        .unwrap()
+        .set_data(Some(AimPrefab(PrefabRef::Index(turret + 1))));
+
+    let removed = prefab.remove_subtree(1).unwrap();
+    assert_eq!(removed.len(), 1);
+    assert_eq!(aim(&prefab, 0), Some(&PrefabRef::Index(turret)));
+    assert_eq!(aim(&prefab, turret - 1), Some(&PrefabRef::Index(turret)));
+
+    let subtree = prefab.remove_subtree(turret - 1).unwrap();
+    assert_eq!(aim(&subtree, 0), Some(&PrefabRef::Index(1)));
+}
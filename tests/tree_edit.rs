@@ -0,0 +1,110 @@
+use bevy_prfb::*;
+
+// root
+// ├── arm
+// │   └── hand
+// └── leg
+//     └── foot
+fn body() -> Prefab<()> {
+    let mut prefab = Prefab::new();
+    prefab.get_entity_mut(0).unwrap().set_name("root");
+    for (name, parent) in [("arm", 0), ("hand", 1), ("leg", 0), ("foot", 3)] {
+        let index = prefab.add(Some(parent), None);
+        prefab.get_entity_mut(index).unwrap().set_name(name);
+    }
+    prefab
+}
+
+fn name(prefab: &Prefab<()>, index: usize) -> &str {
+    prefab.get_entity(index).unwrap().get_name().unwrap()
+}
+
+fn names(prefab: &Prefab<()>, indices: impl Iterator<Item = usize>) -> Vec<&str> {
+    indices.map(|index| name(prefab, index)).collect()
+}
+
+#[test]
+fn traversals_visit_the_descendants() {
+    let prefab = body();
+    assert_eq!(
+        names(&prefab, prefab.depth_first(0)),
+        ["root", "arm", "hand", "leg", "foot"]
+    );
+    assert_eq!(
+        names(&prefab, prefab.breadth_first(0)),
+        ["root", "arm", "leg", "hand", "foot"]
+    );
+    assert_eq!(names(&prefab, prefab.children_of(0)), ["arm", "leg"]);
+    assert_eq!(prefab.parent_of(4), Some(3));
+}
+
+#[test]
+fn reparent_moves_the_descendants_too() {
+    let mut prefab = body();
+    prefab.reparent(1, 3).unwrap();
+
+    assert_eq!(names(&prefab, prefab.children_of(3)), ["arm", "foot"]);
+    assert_eq!(
+        names(&prefab, prefab.depth_first(3)),
+        ["leg", "arm", "hand", "foot"]
+    );
+    assert!(prefab.validate().is_ok());
+}
+
+#[test]
+fn reparent_rejects_a_descendant_or_the_root() {
+    let mut prefab = body();
+    assert!(matches!(prefab.reparent(1, 2), Err(PrefabError::Cycle(1))));
+    assert!(matches!(prefab.reparent(1, 1), Err(PrefabError::Cycle(1))));
+    assert!(matches!(
+        prefab.reparent(0, 1),
+        Err(PrefabError::InvalidEntity { entity: 0, .. })
+    ));
+    assert!(matches!(
+        prefab.reparent(1, 9),
+        Err(PrefabError::MissingParent {
+            entity: 1,
+            parent: 9
+        })
+    ));
+    // Nothing changed
+    assert_eq!(prefab.parent_of(1), Some(0));
+}
+
+#[test]
+fn remove_subtree_renumbers_the_remaining_entities() {
+    let mut prefab = body();
+    let arm = prefab.remove_subtree(1).unwrap();
+
+    assert_eq!(names(&arm, 0..arm.len()), ["arm", "hand"]);
+    assert_eq!(arm.parent_of(0), None);
+    assert_eq!(arm.parent_of(1), Some(0));
+
+    assert_eq!(names(&prefab, 0..prefab.len()), ["root", "leg", "foot"]);
+    assert_eq!(prefab.parent_of(1), Some(0));
+    assert_eq!(prefab.parent_of(2), Some(1));
+    assert!(prefab.validate().is_ok());
+
+    assert!(prefab.remove_subtree(0).is_none());
+    assert!(prefab.remove_subtree(9).is_none());
+}
+
+#[test]
+fn insert_subtree_appends_under_the_parent() {
+    let mut prefab = body();
+    let arm = prefab.remove_subtree(1).unwrap();
+    let index = prefab.insert_subtree(1, arm).unwrap();
+
+    assert_eq!(index, 3);
+    assert_eq!(
+        names(&prefab, prefab.depth_first(0)),
+        ["root", "leg", "foot", "arm", "hand"]
+    );
+    assert_eq!(prefab.parent_of(4), Some(3));
+    assert!(prefab.validate().is_ok());
+
+    assert!(matches!(
+        prefab.insert_subtree(9, body()),
+        Err(PrefabError::MissingEntity(9))
+    ));
+}
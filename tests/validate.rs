@@ -0,0 +1,74 @@
+use bevy_prfb::*;
+
+// The root with two children, the first one with a child of its own
+fn prefab() -> Prefab<()> {
+    let mut prefab = Prefab::new();
+    let first = prefab.add(Some(0), None);
+    prefab.add(Some(first), None);
+    prefab.add(Some(0), None);
+    prefab
+}
+
+#[test]
+fn valid_prefab_has_no_errors() {
+    assert!(prefab().validate().is_ok());
+    assert!(prefab().validate_forest().is_ok());
+}
+
+#[test]
+fn missing_parent_is_reported() {
+    let mut prefab = prefab();
+    prefab.add(Some(9), None);
+
+    let errors = prefab.validate().unwrap_err();
+    assert!(matches!(
+        errors[..],
+        [PrefabError::MissingParent {
+            entity: 4,
+            parent: 9
+        }]
+    ));
+}
+
+#[test]
+fn cycle_is_reported_once_and_its_children_are_unreachable() {
+    let mut prefab = prefab();
+    prefab.add(Some(5), None);
+    prefab.add(Some(4), None);
+    prefab.add(Some(5), None);
+
+    let errors = prefab.validate().unwrap_err();
+    assert!(matches!(
+        errors[..],
+        [PrefabError::Cycle(4), PrefabError::Unreachable(6)]
+    ));
+}
+
+#[test]
+fn extra_root_is_only_valid_in_a_forest() {
+    let mut prefab = prefab();
+    let other = prefab.add(None, None);
+    prefab.add(Some(other), None);
+
+    let errors = prefab.validate().unwrap_err();
+    assert!(matches!(
+        errors[..],
+        [PrefabError::ExtraRoot(4), PrefabError::Unreachable(5)]
+    ));
+    assert!(prefab.validate_forest().is_ok());
+}
+
+#[test]
+fn root_with_a_parent_is_invalid() {
+    let mut prefab = prefab();
+    prefab.get_entity_mut(0).unwrap().set_parent(3);
+
+    let errors = prefab.validate().unwrap_err();
+    assert!(matches!(
+        errors[..],
+        [
+            PrefabError::InvalidEntity { entity: 0, .. },
+            PrefabError::Cycle(0)
+        ]
+    ));
+}
@@ -0,0 +1,119 @@
+use std::fs;
+
+use bevy::prelude::*;
+use bevy_prfb::*;
+use serde::{Deserialize, Serialize};
+
+// Only the prefab is checked, the component is never spawned
+#[allow(dead_code)]
+#[derive(Component)]
+struct Value(i32);
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+struct ValuePrefab(i32);
+impl IntoComponent for ValuePrefab {
+    type Component = Value;
+    fn into_component(self) -> Self::Component {
+        Value(self.0)
+    }
+}
+
+fn data(prefab: &Prefab<ValuePrefab>, index: usize) -> Option<i32> {
+    prefab.get_entity(index)?.get_data().map(|data| data.0)
+}
+
+#[test]
+fn variant_with_two_roots_adds_the_other_root() {
+    let mut base = Prefab::from_data(Some(ValuePrefab(1)));
+    base.add(Some(0), Some(ValuePrefab(2)));
+
+    let mut variant = Prefab::from_data(Some(ValuePrefab(10)));
+    let other = variant.add(None, Some(ValuePrefab(20)));
+    variant.add(Some(other), Some(ValuePrefab(30)));
+
+    let prefab = base.apply_variant(variant).unwrap();
+    assert_eq!(prefab.roots().count(), 2);
+    assert_eq!(data(&prefab, 0), Some(10));
+    assert_eq!(data(&prefab, 1), Some(2));
+    let other = prefab.roots().nth(1).unwrap();
+    assert_eq!(data(&prefab, other), Some(20));
+    assert_eq!(prefab.all_parents_childs()[&other], vec![3]);
+    assert_eq!(data(&prefab, 3), Some(30));
+}
+
+// root(1)
+// └── head(2)
+//     └── (3)
+fn goblin() -> Prefab<ValuePrefab> {
+    let mut base = Prefab::from_data(Some(ValuePrefab(1)));
+    let head = base.add(Some(0), Some(ValuePrefab(2)));
+    base.get_entity_mut(head).unwrap().set_name("head");
+    base.add(Some(head), Some(ValuePrefab(3)));
+    base
+}
+
+#[test]
+fn overrides_change_the_entities_of_the_base() {
+    let mut variant = Prefab::new();
+    let head = variant.add_override("head", Some(ValuePrefab(20)));
+    variant.add(Some(head), Some(ValuePrefab(40)));
+    variant.add_override("head/0", None);
+    variant.add(Some(0), Some(ValuePrefab(50)));
+
+    let prefab = goblin().apply_variant(variant).unwrap();
+    // No data in the variant keeps the data of the base
+    assert_eq!(data(&prefab, 0), Some(1));
+    assert_eq!(data(&prefab, 1), Some(20));
+    assert_eq!(data(&prefab, 2), Some(3));
+    assert_eq!(data(&prefab, 3), Some(40));
+    assert_eq!(data(&prefab, 4), Some(50));
+    let children = prefab.all_parents_childs();
+    assert_eq!(children[&0], vec![1, 4]);
+    assert_eq!(children[&1], vec![2, 3]);
+}
+
+#[test]
+fn override_of_a_missing_path_fails() {
+    let mut variant = Prefab::new();
+    variant.add_override("head/arm", Some(ValuePrefab(20)));
+
+    let result = goblin().apply_variant(variant);
+    assert!(matches!(result, Err(PrefabError::MissingPath(path)) if path == "head/arm"));
+}
+
+#[test]
+fn variant_data_is_merged_into_the_base() {
+    let base = Prefab::from_data(Some((Some(ValuePrefab(1)), Some(ValuePrefab(2)))));
+    let variant = Prefab::from_data(Some((None, Some(ValuePrefab(20)))));
+
+    let prefab = base.apply_variant(variant).unwrap();
+    assert_eq!(
+        prefab.get_entity(0).unwrap().get_data(),
+        Some(&(Some(ValuePrefab(1)), Some(ValuePrefab(20))))
+    );
+}
+
+#[test]
+fn variant_file_is_loaded_over_its_base() {
+    let dir = std::env::temp_dir().join("bevy_prfb_variant");
+    fs::create_dir_all(&dir).unwrap();
+    PrefabLoader::save_prefab::<_, RonPrefabFormat<_>, _>(&goblin(), dir.join("goblin.ron"))
+        .unwrap();
+    fs::write(
+        dir.join("boss.ron"),
+        r#"(
+            base: Some("goblin.ron"),
+            data: Some((10)),
+            overrides: [(entity: "head", children: [(data: Some((30)))])],
+        )"#,
+    )
+    .unwrap();
+
+    let prefab =
+        PrefabLoader::create_prefab::<ValuePrefab, RonPrefabFormat<_>, _>(dir.join("boss.ron"))
+            .unwrap();
+    assert_eq!(prefab.len(), 4);
+    assert_eq!(data(&prefab, 0), Some(10));
+    assert_eq!(data(&prefab, 1), Some(2));
+    assert_eq!(prefab.all_parents_childs()[&1], vec![2, 3]);
+    assert_eq!(data(&prefab, 3), Some(30));
+}